@@ -33,7 +33,7 @@ const main = async () => {
   const msg = {
     modify_launch: {
       contract_address: nftContractAddress,
-      whitelist_merkle_root
+      whitelist_merkle_root: { set: whitelist_merkle_root }
     }
   };
 
@@ -47,7 +47,7 @@ const main = async () => {
 
   const whitelist_merkle_root = tree.getHexRoot().replace('0x', '')
 
-  if (whitelist_merkle_root != launch.whitelist_merkle_root) {
+  if (whitelist_merkle_root != launch.launch.whitelist_merkle_root) {
     throw new Error("Whitelist merkle root is not the same")
   }
 
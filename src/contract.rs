@@ -1,11 +1,27 @@
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
-use cosmwasm_std::{to_binary, Binary, Deps, DepsMut, Env, MessageInfo, Response, StdResult};
+use cosmwasm_std::{
+    to_binary, Addr, Api, Binary, Coin, ContractInfoResponse, Deps, DepsMut, Env, MessageInfo,
+    Order, QuerierWrapper, Response, StdError, StdResult, Storage, Uint128, WasmQuery,
+};
 use cw2::set_contract_version;
+use cw_storage_plus::Map;
 
 use crate::error::ContractError;
-use crate::msg::{ExecuteMsg, InstantiateMsg, MigrateMsg, QueryMsg};
-use crate::state::{NATIVE_DENOM, TAKERADDRESS, TAKERFEE};
+use crate::merkle::{parse_hash, validate_merkle_root, verify_merkle_proof};
+use crate::msg::{
+    AllowlistEntryInfo, BalanceGateQueryMsg, BalanceResponse, ExecuteMsg, InstantiateMsg,
+    MigrateMsg, QueryMsg, RandomnessCallback, ReceiveNftMsg, StakedBalanceAtHeightResponse, Update,
+    Voucher, WhitelistAllocation,
+};
+use crate::state::{
+    BalanceGate, BalanceSource, FeeTier, HolderGate, Launch, LeafFormat, LockPolicy, MerkleScheme,
+    MintLimits, Phase, RaffleConfig, RaffleEntry, SignatureAlgorithm, Tier, TokenIdScheme, TradeIn,
+    VoucherSigner, ALLOWLIST, BLOCK_MINTS, GATE_TOKEN_MINTS, NATIVE_DENOM, RAFFLES, TAKERADDRESS,
+    TAKERFEE, TIER_MINTED, TOKEN_IDS, VOUCHER_USES,
+};
+use crate::uri::{render_token_uri, uses_shuffled_id, validate_uri_template, TokenUriVars};
+use cw721::{Cw721QueryMsg, OwnerOfResponse, TokensResponse};
 use cw721_rewards::{helpers::Cw721Contract, msg::ExecuteMsg as Cw721ExecuteMsg};
 
 // version info for migration info
@@ -48,7 +64,26 @@ pub fn execute(
             contract_address,
             receiver_address,
             proof,
-        } => execute::mint(deps, env, info, contract_address, receiver_address, proof),
+            referrer,
+            voucher,
+            allocation,
+            quantity,
+            gate_token,
+            tier,
+        } => execute::mint(
+            deps,
+            env,
+            info,
+            contract_address,
+            receiver_address,
+            proof,
+            referrer,
+            voucher,
+            allocation,
+            quantity,
+            gate_token,
+            tier,
+        ),
         ExecuteMsg::AddLaunch {
             owner_address,
             contract_address,
@@ -56,6 +91,7 @@ pub fn execute(
             base_uri,
             is_base_uri_static,
             media_extension,
+            uri_template,
             whitelist_price,
             whitelist_max_buy,
             whitelist_started_at,
@@ -67,6 +103,23 @@ pub fn execute(
             royalty_percentage,
             royalty_payment_address,
             whitelist_merkle_root,
+            referral,
+            whitelist_signer,
+            whitelist_leaf_format,
+            whitelist_merkle_scheme,
+            lock_policy,
+            reserved_supply,
+            free_mint_fee,
+            mint_limits,
+            whitelist_holder_gate,
+            public_holder_gate,
+            trade_in,
+            whitelist_balance_gate,
+            public_balance_gate,
+            raffle,
+            tiers,
+            token_id_scheme,
+            provenance_hash,
         } => execute::add_launch(
             deps,
             env,
@@ -77,6 +130,7 @@ pub fn execute(
             base_uri,
             is_base_uri_static,
             media_extension,
+            uri_template,
             whitelist_price,
             whitelist_max_buy,
             whitelist_started_at,
@@ -88,16 +142,35 @@ pub fn execute(
             royalty_percentage,
             royalty_payment_address,
             whitelist_merkle_root,
+            referral,
+            whitelist_signer,
+            whitelist_leaf_format,
+            whitelist_merkle_scheme,
+            lock_policy,
+            reserved_supply,
+            free_mint_fee,
+            mint_limits,
+            whitelist_holder_gate,
+            public_holder_gate,
+            trade_in,
+            whitelist_balance_gate,
+            public_balance_gate,
+            raffle,
+            tiers,
+            token_id_scheme,
+            provenance_hash,
         ),
-        ExecuteMsg::RemoveLaunch { contract_address } => {
-            execute::remove_launch(deps, info, contract_address)
-        }
+        ExecuteMsg::RemoveLaunch {
+            contract_address,
+            reason,
+        } => execute::remove_launch(deps, env, info, contract_address, reason),
         ExecuteMsg::ModifyLaunch {
             contract_address,
             max_supply,
             base_uri,
             is_base_uri_static,
             media_extension,
+            uri_template,
             whitelist_price,
             whitelist_max_buy,
             whitelist_started_at,
@@ -106,7 +179,27 @@ pub fn execute(
             public_max_buy,
             public_started_at,
             public_ended_at,
+            royalty_percentage,
+            royalty_payment_address,
             whitelist_merkle_root,
+            referral,
+            whitelist_signer,
+            whitelist_leaf_format,
+            whitelist_merkle_scheme,
+            lock_policy,
+            reserved_supply,
+            free_mint_fee,
+            mint_limits,
+            whitelist_holder_gate,
+            public_holder_gate,
+            trade_in,
+            whitelist_balance_gate,
+            public_balance_gate,
+            raffle,
+            tiers,
+            token_id_scheme,
+            provenance_hash,
+            force,
         } => execute::modify_launch(
             deps,
             env,
@@ -116,6 +209,7 @@ pub fn execute(
             base_uri,
             is_base_uri_static,
             media_extension,
+            uri_template,
             whitelist_price,
             whitelist_max_buy,
             whitelist_started_at,
@@ -124,22 +218,130 @@ pub fn execute(
             public_max_buy,
             public_started_at,
             public_ended_at,
+            royalty_percentage,
+            royalty_payment_address,
             whitelist_merkle_root,
+            referral,
+            whitelist_signer,
+            whitelist_leaf_format,
+            whitelist_merkle_scheme,
+            lock_policy,
+            reserved_supply,
+            free_mint_fee,
+            mint_limits,
+            whitelist_holder_gate,
+            public_holder_gate,
+            trade_in,
+            whitelist_balance_gate,
+            public_balance_gate,
+            raffle,
+            tiers,
+            token_id_scheme,
+            provenance_hash,
+            force.unwrap_or(false),
         ),
+        ExecuteMsg::FinalizeLaunch { contract_address } => {
+            execute::finalize_launch(deps, env, contract_address)
+        }
+        ExecuteMsg::SetStartingIndex { contract_address } => {
+            execute::set_starting_index(deps, env, contract_address)
+        }
+        ExecuteMsg::LockLaunch { contract_address } => {
+            execute::lock_launch(deps, env, info, contract_address)
+        }
+        ExecuteMsg::AirdropMint {
+            contract_address,
+            recipients,
+        } => execute::airdrop_mint(deps, env, info, contract_address, recipients),
+        ExecuteMsg::TransferLaunchOwnership {
+            contract_address,
+            new_owner,
+            expiry,
+        } => {
+            execute::transfer_launch_ownership(deps, env, info, contract_address, new_owner, expiry)
+        }
+        ExecuteMsg::AcceptLaunchOwnership { contract_address } => {
+            execute::accept_launch_ownership(deps, env, info, contract_address)
+        }
         ExecuteMsg::ChangeTakerFee { taker_fee } => {
             execute::change_taker_fee(deps, info, taker_fee)
         }
+        ExecuteMsg::ChangeLaunchTakerFee {
+            contract_address,
+            taker_fee,
+            taker_fee_tiers,
+        } => execute::change_launch_taker_fee(
+            deps,
+            info,
+            contract_address,
+            taker_fee,
+            taker_fee_tiers,
+        ),
+        ExecuteMsg::WithdrawReferralRewards {} => execute::withdraw_referral_rewards(deps, info),
+        ExecuteMsg::ReceiveNft(receive_msg) => execute::receive_nft(deps, env, info, receive_msg),
+        ExecuteMsg::DepositTradeInFunds { contract_address } => {
+            execute::deposit_trade_in_funds(deps, info, contract_address)
+        }
+        ExecuteMsg::WithdrawTradeInFunds { contract_address } => {
+            execute::withdraw_trade_in_funds(deps, info, contract_address)
+        }
+        ExecuteMsg::Register { contract_address } => {
+            execute::register(deps, env, info, contract_address)
+        }
+        ExecuteMsg::DrawRaffle {
+            contract_address,
+            seed,
+        } => execute::draw_raffle(deps, env, info, contract_address, seed),
+        ExecuteMsg::CancelRaffleDraw { contract_address } => {
+            execute::cancel_raffle_draw(deps, info, contract_address)
+        }
+        ExecuteMsg::ClaimRaffleMint { contract_address } => {
+            execute::claim_raffle_mint(deps, env, info, contract_address)
+        }
+        ExecuteMsg::WithdrawRaffleDeposit { contract_address } => {
+            execute::withdraw_raffle_deposit(deps, env, info, contract_address)
+        }
+        ExecuteMsg::SetRandomnessBeacon { beacon } => {
+            execute::set_randomness_beacon(deps, info, beacon)
+        }
+        ExecuteMsg::NoisReceive { callback } => {
+            execute::receive_randomness(deps, env, info, callback)
+        }
+        ExecuteMsg::AddTokenIds {
+            contract_address,
+            token_ids,
+        } => execute::add_token_ids(deps, info, contract_address, token_ids),
+        ExecuteMsg::AddToAllowlist {
+            contract_address,
+            phase,
+            entries,
+        } => execute::add_to_allowlist(deps, info, contract_address, phase, entries),
+        ExecuteMsg::RemoveFromAllowlist {
+            contract_address,
+            phase,
+            addresses,
+        } => execute::remove_from_allowlist(deps, env, info, contract_address, phase, addresses),
     }
 }
 
 pub mod execute {
     use std::marker::PhantomData;
 
-    use cosmwasm_std::{coins, Addr, BankMsg, Coin, Decimal, Empty, Uint128, Uint64};
+    use cosmwasm_std::{
+        coins, from_binary, BankMsg, Coin, CosmosMsg, Decimal, Empty, Uint128, Uint64, WasmMsg,
+    };
+    use cw721::Cw721ReceiveMsg;
     use cw721_rewards::Metadata;
-    use cw_storage_plus::Map;
 
-    use crate::state::{Launch, LAUNCHES};
+    use cw_utils::Expiration;
+
+    use crate::randomness::{self, request_randomness};
+    use crate::state::{
+        AllowlistEntry, ArchivedLaunch, PendingLaunchOwner, RaffleState, RandomnessJob,
+        ReferralConfig, ARCHIVED_LAUNCHES, LAUNCHES, PENDING_LAUNCH_OWNERS, RAFFLE_ENTRIES,
+        RAFFLE_REGISTRANTS, RANDOMNESS_BEACON, REFERRAL_REWARDS, REFERRAL_TOTALS, TOKEN_ID_COUNT,
+        TOKEN_ID_POSITIONS, TRADE_IN_DEPOSITS,
+    };
 
     use super::*;
 
@@ -155,14 +357,15 @@ pub mod execute {
 
     pub fn add_launch(
         deps: DepsMut,
-        _env: Env,
+        env: Env,
         info: MessageInfo,
         owner_address: String,
         contract_address: String,
-        max_supply: u64,
+        max_supply: Option<u64>,
         base_uri: String,
         is_base_uri_static: bool,
         media_extension: Option<String>,
+        uri_template: Option<String>,
         whitelist_price: Coin,
         whitelist_max_buy: Option<u16>,
         whitelist_started_at: Uint64,
@@ -174,6 +377,23 @@ pub mod execute {
         royalty_percentage: Option<u64>,
         royalty_payment_address: Option<String>,
         whitelist_merkle_root: Option<String>,
+        referral: Option<ReferralConfig>,
+        whitelist_signer: Option<VoucherSigner>,
+        whitelist_leaf_format: Option<LeafFormat>,
+        whitelist_merkle_scheme: Option<MerkleScheme>,
+        lock_policy: Option<LockPolicy>,
+        reserved_supply: Option<u64>,
+        free_mint_fee: Option<Coin>,
+        mint_limits: Option<MintLimits>,
+        whitelist_holder_gate: Option<HolderGate>,
+        public_holder_gate: Option<HolderGate>,
+        trade_in: Option<TradeIn>,
+        whitelist_balance_gate: Option<BalanceGate>,
+        public_balance_gate: Option<BalanceGate>,
+        raffle: Option<RaffleConfig>,
+        tiers: Option<Vec<Tier>>,
+        token_id_scheme: Option<TokenIdScheme>,
+        provenance_hash: Option<String>,
     ) -> Result<Response, ContractError> {
         cw_ownable::assert_owner(deps.storage, &info.sender)?;
 
@@ -186,6 +406,14 @@ pub mod execute {
             return Err(ContractError::LaunchAlreadyExist {});
         }
 
+        // resume the counter of a removed launch so token ids do not collide
+        let archived = ARCHIVED_LAUNCHES.may_load(deps.storage, &contract_address)?;
+        if let Some(ref archived) = archived {
+            if archived.launch.token_id_scheme != token_id_scheme {
+                return Err(ContractError::InvalidTokenIdScheme {});
+            }
+        }
+
         let native_denom = NATIVE_DENOM.load(deps.storage)?;
         if whitelist_price.denom != native_denom {
             return Err(ContractError::DenomNotSupported {});
@@ -193,30 +421,107 @@ pub mod execute {
         if public_price.denom != native_denom {
             return Err(ContractError::DenomNotSupported {});
         }
+        if let Some(ref free_mint_fee) = free_mint_fee {
+            if free_mint_fee.denom != native_denom {
+                return Err(ContractError::DenomNotSupported {});
+            }
+        }
+        if let Some(ref tiers) = tiers {
+            validate_tiers(tiers, &native_denom)?;
+        }
+        if let Some(ref referral) = referral {
+            if referral.share_percentage > 100 {
+                return Err(ContractError::InvalidReferralShare {});
+            }
+        }
+        if let Some(ref whitelist_signer) = whitelist_signer {
+            validate_voucher_signer(whitelist_signer)?;
+        }
+        for holder_gate in [&whitelist_holder_gate, &public_holder_gate]
+            .into_iter()
+            .flatten()
+        {
+            validate_holder_gate(deps.api, holder_gate)?;
+        }
+        if let Some(ref trade_in) = trade_in {
+            validate_trade_in(deps.api, trade_in)?;
+        }
+        for balance_gate in [&whitelist_balance_gate, &public_balance_gate]
+            .into_iter()
+            .flatten()
+        {
+            validate_balance_gate(deps.api, &env, balance_gate)?;
+        }
+        if let Some(ref whitelist_merkle_root) = whitelist_merkle_root {
+            validate_merkle_root(whitelist_merkle_root)?;
+        }
 
-        LAUNCHES.save(
-            deps.storage,
-            &contract_address,
-            &Launch {
-                owner_address,
-                max_supply,
-                base_uri,
-                is_base_uri_static,
-                media_extension,
-                whitelist_price: whitelist_price,
-                whitelist_max_buy,
-                whitelist_started_at: whitelist_started_at.u64(),
-                whitelist_ended_at: whitelist_ended_at.u64(),
-                public_price: public_price,
-                public_max_buy,
-                public_started_at: public_started_at.u64(),
-                public_ended_at: public_ended_at.u64(),
-                last_token_id: 0,
-                royalty_percentage,
-                royalty_payment_address,
-                whitelist_merkle_root,
-            },
-        )?;
+        let mut launch = Launch {
+            owner_address,
+            max_supply,
+            base_uri,
+            is_base_uri_static,
+            media_extension,
+            uri_template,
+            whitelist_price: whitelist_price,
+            whitelist_max_buy,
+            whitelist_started_at: whitelist_started_at.u64(),
+            whitelist_ended_at: whitelist_ended_at.u64(),
+            public_price: public_price,
+            public_max_buy,
+            public_started_at: public_started_at.u64(),
+            public_ended_at: public_ended_at.u64(),
+            last_token_id: archived
+                .as_ref()
+                .map_or(0, |archived| archived.launch.last_token_id),
+            royalty_percentage,
+            royalty_payment_address,
+            whitelist_merkle_root,
+            taker_fee: None,
+            taker_fee_tiers: None,
+            referral,
+            whitelist_signer,
+            whitelist_leaf_format,
+            whitelist_merkle_scheme,
+            lock_policy,
+            locked_at: archived
+                .as_ref()
+                .and_then(|archived| archived.launch.locked_at),
+            reserved_supply,
+            reserved_minted: archived
+                .as_ref()
+                .map_or(0, |archived| archived.launch.reserved_minted),
+            sold: 0,
+            free_mint_fee,
+            mint_limits,
+            whitelist_holder_gate,
+            public_holder_gate,
+            trade_in,
+            whitelist_balance_gate,
+            public_balance_gate,
+            raffle,
+            edition_size: archived
+                .as_ref()
+                .and_then(|archived| archived.launch.edition_size),
+            tiers,
+            token_id_scheme,
+            provenance_hash,
+            starting_index: None,
+        };
+        // a re-added launch is held to the lock it was removed under
+        if let Some(archived) = archived {
+            check_launch_lock(&archived.launch, &launch)?;
+            if archived.launch.provenance_hash == launch.provenance_hash {
+                launch.starting_index = archived.launch.starting_index;
+            }
+        }
+        validate_launch(deps.storage, &contract_address, &launch)?;
+        // stored entries can only be added once the launch exists, before its whitelist opens
+        let now = env.block.time.seconds();
+        if now >= launch.whitelist_started_at {
+            validate_whitelist_source(deps.storage, &contract_address, &launch, now)?;
+        }
+        LAUNCHES.save(deps.storage, &contract_address, &launch)?;
 
         Ok(Response::new()
             .add_attribute("action", "add_launch")
@@ -225,34 +530,49 @@ pub mod execute {
 
     pub fn modify_launch(
         deps: DepsMut,
-        _env: Env,
+        env: Env,
         info: MessageInfo,
         contract_address: String,
-        max_supply: Option<u64>,
+        max_supply: Update<u64>,
         base_uri: Option<String>,
         is_base_uri_static: Option<bool>,
-        media_extension: Option<String>,
+        media_extension: Update<String>,
+        uri_template: Update<String>,
         whitelist_price: Option<Coin>,
-        whitelist_max_buy: Option<u16>,
+        whitelist_max_buy: Update<u16>,
         whitelist_started_at: Option<Uint64>,
         whitelist_ended_at: Option<Uint64>,
         public_price: Option<Coin>,
-        public_max_buy: Option<u16>,
+        public_max_buy: Update<u16>,
         public_started_at: Option<Uint64>,
         public_ended_at: Option<Uint64>,
-        whitelist_merkle_root: Option<String>,
+        royalty_percentage: Update<u64>,
+        royalty_payment_address: Update<String>,
+        whitelist_merkle_root: Update<String>,
+        referral: Update<ReferralConfig>,
+        whitelist_signer: Update<VoucherSigner>,
+        whitelist_leaf_format: Update<LeafFormat>,
+        whitelist_merkle_scheme: Update<MerkleScheme>,
+        lock_policy: Update<LockPolicy>,
+        reserved_supply: Update<u64>,
+        free_mint_fee: Update<Coin>,
+        mint_limits: Update<MintLimits>,
+        whitelist_holder_gate: Update<HolderGate>,
+        public_holder_gate: Update<HolderGate>,
+        trade_in: Update<TradeIn>,
+        whitelist_balance_gate: Update<BalanceGate>,
+        public_balance_gate: Update<BalanceGate>,
+        raffle: Update<RaffleConfig>,
+        tiers: Update<Vec<Tier>>,
+        token_id_scheme: Update<TokenIdScheme>,
+        provenance_hash: Update<String>,
+        force: bool,
     ) -> Result<Response, ContractError> {
         let contract_address = deps.api.addr_validate(&contract_address)?;
 
-        let launch = LAUNCHES.load(deps.storage, &contract_address)?;
-
-        let is_owner = cw_ownable::assert_owner(deps.storage, &info.sender);
+        let current = LAUNCHES.load(deps.storage, &contract_address)?;
 
-        if is_owner.is_err() {
-            if info.sender != launch.owner_address {
-                return Err(ContractError::Unauthorized {});
-            }
-        }
+        assert_launch_owner_or_admin(deps.storage, &info.sender, &current)?;
 
         let native_denom = NATIVE_DENOM.load(deps.storage)?;
         if let Some(ref whitelist_price) = whitelist_price {
@@ -265,425 +585,5943 @@ pub mod execute {
                 return Err(ContractError::DenomNotSupported {});
             }
         }
+        if let Update::Set(ref free_mint_fee) = free_mint_fee {
+            if free_mint_fee.denom != native_denom {
+                return Err(ContractError::DenomNotSupported {});
+            }
+        }
+        if let Update::Set(ref tiers) = tiers {
+            validate_tiers(tiers, &native_denom)?;
+        }
+        if let Update::Set(ref referral) = referral {
+            if referral.share_percentage > 100 {
+                return Err(ContractError::InvalidReferralShare {});
+            }
+        }
+        if let Update::Set(ref whitelist_signer) = whitelist_signer {
+            validate_voucher_signer(whitelist_signer)?;
+        }
+        for holder_gate in [&whitelist_holder_gate, &public_holder_gate] {
+            if let Update::Set(holder_gate) = holder_gate {
+                validate_holder_gate(deps.api, holder_gate)?;
+            }
+        }
+        if let Update::Set(ref trade_in) = trade_in {
+            validate_trade_in(deps.api, trade_in)?;
+        }
+        for balance_gate in [&whitelist_balance_gate, &public_balance_gate] {
+            if let Update::Set(balance_gate) = balance_gate {
+                validate_balance_gate(deps.api, &env, balance_gate)?;
+            }
+        }
+        if let Update::Set(ref whitelist_merkle_root) = whitelist_merkle_root {
+            validate_merkle_root(whitelist_merkle_root)?;
+        }
+
+        if current.edition_size.is_some() {
+            return Err(ContractError::LaunchFinalized {});
+        }
+
+        let mut launch = current.clone();
+        max_supply.apply(&mut launch.max_supply);
+        if let Some(base_uri) = base_uri {
+            launch.base_uri = base_uri;
+        }
+        if let Some(is_base_uri_static) = is_base_uri_static {
+            launch.is_base_uri_static = is_base_uri_static;
+        }
+        if let Some(whitelist_price) = whitelist_price {
+            launch.whitelist_price = whitelist_price;
+        }
+        if let Some(whitelist_started_at) = whitelist_started_at {
+            launch.whitelist_started_at = whitelist_started_at.u64();
+        }
+        if let Some(whitelist_ended_at) = whitelist_ended_at {
+            launch.whitelist_ended_at = whitelist_ended_at.u64();
+        }
+        if let Some(public_price) = public_price {
+            launch.public_price = public_price;
+        }
+        if let Some(public_started_at) = public_started_at {
+            launch.public_started_at = public_started_at.u64();
+        }
+        if let Some(public_ended_at) = public_ended_at {
+            launch.public_ended_at = public_ended_at.u64();
+        }
+        media_extension.apply(&mut launch.media_extension);
+        uri_template.apply(&mut launch.uri_template);
+        whitelist_max_buy.apply(&mut launch.whitelist_max_buy);
+        public_max_buy.apply(&mut launch.public_max_buy);
+        royalty_percentage.apply(&mut launch.royalty_percentage);
+        royalty_payment_address.apply(&mut launch.royalty_payment_address);
+        whitelist_merkle_root.apply(&mut launch.whitelist_merkle_root);
+        referral.apply(&mut launch.referral);
+        whitelist_signer.apply(&mut launch.whitelist_signer);
+        whitelist_leaf_format.apply(&mut launch.whitelist_leaf_format);
+        whitelist_merkle_scheme.apply(&mut launch.whitelist_merkle_scheme);
+        lock_policy.apply(&mut launch.lock_policy);
+        reserved_supply.apply(&mut launch.reserved_supply);
+        free_mint_fee.apply(&mut launch.free_mint_fee);
+        mint_limits.apply(&mut launch.mint_limits);
+        whitelist_holder_gate.apply(&mut launch.whitelist_holder_gate);
+        public_holder_gate.apply(&mut launch.public_holder_gate);
+        trade_in.apply(&mut launch.trade_in);
+        whitelist_balance_gate.apply(&mut launch.whitelist_balance_gate);
+        public_balance_gate.apply(&mut launch.public_balance_gate);
+        raffle.apply(&mut launch.raffle);
+        tiers.apply(&mut launch.tiers);
+        token_id_scheme.apply(&mut launch.token_id_scheme);
+        provenance_hash.apply(&mut launch.provenance_hash);
+
+        // the terms and the seed commitment are fixed once registration opens
+        let raffle_opened = current
+            .raffle
+            .as_ref()
+            .is_some_and(|raffle| env.block.time.seconds() >= raffle.registration_started_at);
+        if launch.raffle != current.raffle && raffle_opened {
+            return Err(ContractError::PhaseActive {});
+        }
+
+        let tier_prices_changed = tier_prices(&launch) != tier_prices(&current);
+
+        // buyers of a live phase must not see its price move under them
+        if !force {
+            let price_changed = match current_phase(&current, env.block.time.seconds()) {
+                Some(Phase::Whitelist) => {
+                    launch.whitelist_price != current.whitelist_price || tier_prices_changed
+                }
+                Some(Phase::Public) => {
+                    launch.public_price != current.public_price || tier_prices_changed
+                }
+                None => false,
+            };
+            if price_changed {
+                return Err(ContractError::PhaseActive {});
+            }
+        }
 
-        LAUNCHES.save(
+        check_launch_lock(&current, &launch)?;
+        validate_launch(deps.storage, &contract_address, &launch)?;
+        validate_whitelist_source(
             deps.storage,
             &contract_address,
-            &Launch {
-                owner_address: launch.owner_address,
-                max_supply: if let Some(max_supply) = max_supply {
-                    max_supply
-                } else {
-                    launch.max_supply
-                },
-                base_uri: if let Some(base_uri) = base_uri {
-                    base_uri
-                } else {
-                    launch.base_uri
-                },
-                is_base_uri_static: if let Some(is_base_uri_static) = is_base_uri_static {
-                    is_base_uri_static
-                } else {
-                    launch.is_base_uri_static
-                },
-                media_extension: if let Some(media_extension) = media_extension {
-                    Some(media_extension)
-                } else {
-                    launch.media_extension
-                },
-                whitelist_price: if let Some(whitelist_price) = whitelist_price {
-                    whitelist_price
-                } else {
-                    launch.whitelist_price
-                },
-                whitelist_max_buy: if let Some(whitelist_max_buy) = whitelist_max_buy {
-                    Some(whitelist_max_buy)
-                } else {
-                    launch.whitelist_max_buy
-                },
-                whitelist_started_at: if let Some(whitelist_started_at) = whitelist_started_at {
-                    whitelist_started_at.u64()
-                } else {
-                    launch.whitelist_started_at
-                },
-                whitelist_ended_at: if let Some(whitelist_ended_at) = whitelist_ended_at {
-                    whitelist_ended_at.u64()
-                } else {
-                    launch.whitelist_ended_at
-                },
-                public_price: if let Some(public_price) = public_price {
-                    public_price
-                } else {
-                    launch.public_price
-                },
-                public_max_buy: if let Some(public_max_buy) = public_max_buy {
-                    Some(public_max_buy)
-                } else {
-                    launch.public_max_buy
-                },
-                public_started_at: if let Some(public_started_at) = public_started_at {
-                    public_started_at.u64()
-                } else {
-                    launch.public_started_at
-                },
-                public_ended_at: if let Some(public_ended_at) = public_ended_at {
-                    public_ended_at.u64()
-                } else {
-                    launch.public_ended_at
-                },
-                last_token_id: launch.last_token_id,
-                royalty_percentage: launch.royalty_percentage,
-                royalty_payment_address: launch.royalty_payment_address,
-                whitelist_merkle_root: if let Some(whitelist_merkle_root) = whitelist_merkle_root {
-                    Some(whitelist_merkle_root)
-                } else {
-                    launch.whitelist_merkle_root
-                },
-            },
+            &launch,
+            env.block.time.seconds(),
         )?;
+        LAUNCHES.save(deps.storage, &contract_address, &launch)?;
 
         Ok(Response::new()
             .add_attribute("action", "modify_launch")
+            .add_attribute("contract_address", contract_address)
+            .add_attribute("changed", changed_fields(&current, &launch).join(",")))
+    }
+
+    pub fn lock_launch(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        contract_address: String,
+    ) -> Result<Response, ContractError> {
+        let contract_address = deps.api.addr_validate(&contract_address)?;
+
+        let mut launch = LAUNCHES.load(deps.storage, &contract_address)?;
+
+        assert_launch_owner_or_admin(deps.storage, &info.sender, &launch)?;
+
+        if launch.locked_at.is_some() {
+            return Err(ContractError::AlreadyLocked {});
+        }
+        launch.locked_at = Some(env.block.time.seconds());
+        LAUNCHES.save(deps.storage, &contract_address, &launch)?;
+
+        Ok(Response::new()
+            .add_attribute("action", "lock_launch")
             .add_attribute("contract_address", contract_address))
     }
 
+    pub fn finalize_launch(
+        deps: DepsMut,
+        env: Env,
+        contract_address: String,
+    ) -> Result<Response, ContractError> {
+        let contract_address = deps.api.addr_validate(&contract_address)?;
+        let mut launch = LAUNCHES.load(deps.storage, &contract_address)?;
+
+        if launch.max_supply.is_some() {
+            return Err(ContractError::InvalidOpenEdition {});
+        }
+        if launch.edition_size.is_some() {
+            return Err(ContractError::LaunchFinalized {});
+        }
+        let sale_ended_at = launch.whitelist_ended_at.max(launch.public_ended_at);
+        if env.block.time.seconds() < sale_ended_at {
+            return Err(ContractError::SaleNotEnded {});
+        }
+
+        launch.edition_size = Some(launch.last_token_id);
+        if launch.provenance_hash.is_some() && launch.starting_index.is_none() {
+            launch.starting_index = Some(starting_index(&env, &contract_address, &launch)?);
+        }
+        LAUNCHES.save(deps.storage, &contract_address, &launch)?;
+
+        Ok(Response::new()
+            .add_attribute("action", "finalize_launch")
+            .add_attribute("contract_address", contract_address)
+            .add_attribute("edition_size", launch.last_token_id.to_string()))
+    }
+
+    pub fn transfer_launch_ownership(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        contract_address: String,
+        new_owner: String,
+        expiry: Option<Expiration>,
+    ) -> Result<Response, ContractError> {
+        let contract_address = deps.api.addr_validate(&contract_address)?;
+
+        let launch = LAUNCHES.load(deps.storage, &contract_address)?;
+
+        assert_launch_owner_or_admin(deps.storage, &info.sender, &launch)?;
+
+        let new_owner = deps.api.addr_validate(&new_owner)?;
+        if let Some(ref expiry) = expiry {
+            if expiry.is_expired(&env.block) {
+                return Err(ContractError::InvalidExpiration {});
+            }
+        }
+
+        // a new transfer replaces any pending one
+        PENDING_LAUNCH_OWNERS.save(
+            deps.storage,
+            &contract_address,
+            &PendingLaunchOwner {
+                new_owner: new_owner.clone(),
+                expiry,
+            },
+        )?;
+
+        Ok(Response::new()
+            .add_attribute("action", "transfer_launch_ownership")
+            .add_attribute("contract_address", contract_address)
+            .add_attribute("pending_owner", new_owner))
+    }
+
+    pub fn accept_launch_ownership(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        contract_address: String,
+    ) -> Result<Response, ContractError> {
+        let contract_address = deps.api.addr_validate(&contract_address)?;
+
+        let mut launch = LAUNCHES.load(deps.storage, &contract_address)?;
+        let pending = PENDING_LAUNCH_OWNERS
+            .may_load(deps.storage, &contract_address)?
+            .ok_or(ContractError::LaunchTransferNotFound {})?;
+
+        if info.sender != pending.new_owner {
+            return Err(ContractError::NotPendingLaunchOwner {});
+        }
+        if let Some(ref expiry) = pending.expiry {
+            if expiry.is_expired(&env.block) {
+                return Err(ContractError::LaunchTransferExpired {});
+            }
+        }
+
+        launch.owner_address = pending.new_owner;
+        LAUNCHES.save(deps.storage, &contract_address, &launch)?;
+        PENDING_LAUNCH_OWNERS.remove(deps.storage, &contract_address);
+
+        Ok(Response::new()
+            .add_attribute("action", "accept_launch_ownership")
+            .add_attribute("contract_address", contract_address)
+            .add_attribute("owner", launch.owner_address))
+    }
+
     pub fn remove_launch(
         deps: DepsMut,
+        env: Env,
         info: MessageInfo,
         contract_address: String,
+        reason: Option<String>,
     ) -> Result<Response, ContractError> {
         cw_ownable::assert_owner(deps.storage, &info.sender)?;
         let contract_address = deps.api.addr_validate(&contract_address)?;
 
+        let launch = LAUNCHES.load(deps.storage, &contract_address)?;
+
+        // registrants claim and withdraw through the live launch
+        let raffle_pending = RAFFLES
+            .may_load(deps.storage, &contract_address)?
+            .is_some_and(|raffle| raffle.pending_job.is_some());
+        let raffle_holds_deposits = RAFFLE_ENTRIES
+            .prefix(&contract_address)
+            .range(deps.storage, None, None, Order::Ascending)
+            .next()
+            .is_some();
+        if raffle_pending || raffle_holds_deposits {
+            return Err(ContractError::RaffleInProgress {});
+        }
+
+        ARCHIVED_LAUNCHES.save(
+            deps.storage,
+            &contract_address,
+            &ArchivedLaunch {
+                launch,
+                reason: reason.clone(),
+                archived_at: env.block.time.seconds(),
+            },
+        )?;
         LAUNCHES.remove(deps.storage, &contract_address);
+        PENDING_LAUNCH_OWNERS.remove(deps.storage, &contract_address);
 
         Ok(Response::new()
             .add_attribute("action", "remove_launch")
-            .add_attribute("contract_address", contract_address))
+            .add_attribute("contract_address", contract_address)
+            .add_attribute("reason", reason.unwrap_or_default()))
     }
 
     pub fn mint(
-        deps: DepsMut,
+        mut deps: DepsMut,
         env: Env,
         info: MessageInfo,
         contract_address: String,
         receiver_address: Option<String>,
         proof: Option<Vec<String>>,
+        referrer: Option<String>,
+        voucher: Option<Voucher>,
+        allocation: Option<WhitelistAllocation>,
+        quantity: Option<u32>,
+        gate_token: Option<String>,
+        tier: Option<String>,
     ) -> Result<Response, ContractError> {
         let contract_address = deps.api.addr_validate(&contract_address)?;
         let mut launch = LAUNCHES.load(deps.storage, &contract_address)?;
 
+        // raffle launches only mint through claims
+        if launch.raffle.is_some() {
+            return Err(ContractError::Closed {});
+        }
+
+        let quantity = u64::from(quantity.unwrap_or(1));
+        if quantity == 0 {
+            return Err(ContractError::InvalidQuantity {});
+        }
+
         // check funds
         let fund_input;
 
-        // check if last_token_id < total_supply
-        if launch.last_token_id >= launch.max_supply {
+        // check if last_token_id < total_supply, keeping the reserve for airdrops
+        if matches!(available_supply(&launch), Some(available) if quantity > available) {
             return Err(ContractError::SoldOut {});
         }
 
+        // tiered launches sell every token out of a tier
+        let tier = match (&launch.tiers, tier) {
+            (Some(tiers), Some(tier)) => Some(
+                tiers
+                    .iter()
+                    .find(|candidate| candidate.name == tier)
+                    .cloned()
+                    .ok_or(ContractError::UnknownTier {})?,
+            ),
+            (None, None) => None,
+            _ => return Err(ContractError::UnknownTier {}),
+        };
+        let tier_minted = match tier {
+            Some(ref tier) => {
+                let minted = TIER_MINTED
+                    .may_load(deps.storage, (&contract_address, &tier.name))?
+                    .unwrap_or_default();
+                if minted + quantity > tier.supply {
+                    return Err(ContractError::SoldOut {});
+                }
+                minted
+            }
+            None => 0,
+        };
+
         let account_address = info.sender.clone();
+
+        if let Some(ref mint_limits) = launch.mint_limits {
+            check_mint_limits(
+                deps.storage,
+                &deps.querier,
+                &env,
+                &contract_address,
+                &account_address,
+                mint_limits,
+                quantity,
+            )?;
+        }
+
         let receiver_address = if let Some(receiver_id) = receiver_address {
             deps.api.addr_validate(receiver_id.as_str())?
         } else {
             account_address.clone()
         };
 
+        let referrer = referrer
+            .map(|referrer| deps.api.addr_validate(&referrer))
+            .transpose()?;
+        if let Some(ref referrer) = referrer {
+            if *referrer == account_address || *referrer == receiver_address {
+                return Err(ContractError::SelfReferral {});
+            }
+        }
+
         // Determine minting status
         let denom;
         let price: Uint128;
         let current_timestamp_in_seconds = env.block.time.seconds();
-        if current_timestamp_in_seconds > launch.whitelist_started_at
-            && current_timestamp_in_seconds < launch.whitelist_ended_at
-        {
+        let phase = current_phase(&launch, current_timestamp_in_seconds);
+        if phase == Some(Phase::Whitelist) {
             // WHITELIST MINT
-            denom = &launch.whitelist_price.denom;
-            price = launch.whitelist_price.amount;
+            denom = launch.whitelist_price.denom.clone();
 
-            fund_input = cw_utils::must_pay(&info, denom).unwrap();
-            // check if user in whitelist
+            // check if user in whitelist, either by the stored allowlist, a signed
+            // voucher or a merkle proof
+            let mut whitelist_allocation = None;
+            let mut balance_max_buy = None;
+            let allowlist_entry = ALLOWLIST.may_load(
+                deps.storage,
+                (
+                    &contract_address,
+                    Phase::Whitelist.as_str(),
+                    &account_address,
+                ),
+            )?;
 
-            let is_whitelisted = verify_merkle_proof(
-                account_address.to_string(),
-                launch.whitelist_merkle_root.as_ref().unwrap(),
-                proof.unwrap(),
-            );
+            let is_whitelisted = if allowlist_entry.is_some() {
+                true
+            } else if let Some(voucher) = voucher {
+                use_voucher(
+                    deps.storage,
+                    deps.api,
+                    current_timestamp_in_seconds,
+                    &launch,
+                    &contract_address,
+                    &account_address,
+                    voucher,
+                    quantity,
+                )?;
+                true
+            } else if let Some(proof) = proof {
+                let merkle_root = launch
+                    .whitelist_merkle_root
+                    .as_ref()
+                    .ok_or(ContractError::MerkleRootNotSet {})?;
+                let leaf = whitelist_leaf(&launch, account_address.as_str(), allocation.as_ref())?;
+                verify_merkle_proof(
+                    launch
+                        .whitelist_merkle_scheme
+                        .as_ref()
+                        .unwrap_or(&MerkleScheme::V1),
+                    contract_address.as_str(),
+                    &leaf,
+                    merkle_root,
+                    &proof,
+                )?;
+                whitelist_allocation = allocation;
+                true
+            } else if launch.whitelist_holder_gate.is_some()
+                || launch.whitelist_balance_gate.is_some()
+            {
+                // every gate of the phase has to pass, as in the public phase
+                balance_max_buy = check_phase_gates(
+                    deps.branch(),
+                    &contract_address,
+                    &launch,
+                    &Phase::Whitelist,
+                    &account_address,
+                    gate_token.as_deref(),
+                    quantity,
+                )?;
+                true
+            } else {
+                false
+            };
 
             if !is_whitelisted {
                 return Err(ContractError::NotWhitelisted {});
             }
 
-            // whitelist
-            if fund_input.u128() < launch.whitelist_price.amount.u128() {
-                return Err(ContractError::InsufficientFunds {});
-            }
+            // whitelist, the wallet allocation overrides the launch wide price and quota,
+            // tiers keep their own price
+            price = match (&tier, &whitelist_allocation) {
+                (Some(tier), _) => tier.price.amount,
+                (None, Some(allocation)) => allocation.price,
+                (None, None) => launch.whitelist_price.amount,
+            };
+            fund_input = collect_payment(&info, &launch, &denom, price, quantity)?;
 
             // check whitelist quota
-            if let Some(whitelist_max_buy) = launch.whitelist_max_buy {
-                let whitelist_items_key = format!("{}-{}", contract_address, "whitelistitems");
-                let whitelist_items: Map<&Addr, u64> = Map::new(whitelist_items_key.as_str());
+            let whitelist_max_buy = allowlist_entry
+                .and_then(|entry| entry.max_quantity)
+                .or(whitelist_allocation.map(|allocation| allocation.max_quantity))
+                .or(balance_max_buy)
+                .map(u64::from)
+                .or(launch.whitelist_max_buy.map(u64::from));
+            if let Some(whitelist_max_buy) = whitelist_max_buy {
+                consume_phase_quota(
+                    deps.storage,
+                    &contract_address,
+                    &Phase::Whitelist,
+                    &receiver_address,
+                    &account_address,
+                    quantity,
+                    whitelist_max_buy,
+                )?;
+            }
+        } else if phase == Some(Phase::Public) {
+            let balance_max_buy = check_phase_gates(
+                deps.branch(),
+                &contract_address,
+                &launch,
+                &Phase::Public,
+                &account_address,
+                gate_token.as_deref(),
+                quantity,
+            )?;
 
-                let previous_items = whitelist_items
-                    .load(deps.storage, &receiver_address)
-                    .unwrap_or(0);
+            denom = launch.public_price.denom.clone();
+            price = tier
+                .as_ref()
+                .map_or(launch.public_price.amount, |tier| tier.price.amount);
+            fund_input = collect_payment(&info, &launch, &denom, price, quantity)?;
 
-                if previous_items >= whitelist_max_buy as u64 {
-                    return Err(ContractError::MintQuotaExhausted {});
-                }
-                whitelist_items.save(deps.storage, &receiver_address, &(previous_items + 1))?;
+            // check public quota, stored allowlist entries may override it
+            let allowlist_entry = ALLOWLIST.may_load(
+                deps.storage,
+                (&contract_address, Phase::Public.as_str(), &account_address),
+            )?;
+            let public_max_buy = allowlist_entry
+                .and_then(|entry| entry.max_quantity)
+                .or(balance_max_buy)
+                .map(u64::from)
+                .or(launch.public_max_buy.map(u64::from));
+            if let Some(public_max_buy) = public_max_buy {
+                consume_phase_quota(
+                    deps.storage,
+                    &contract_address,
+                    &Phase::Public,
+                    &receiver_address,
+                    &account_address,
+                    quantity,
+                    public_max_buy,
+                )?;
             }
-        } else if current_timestamp_in_seconds > launch.public_started_at
-            && current_timestamp_in_seconds < launch.public_ended_at
-        {
-            denom = &launch.public_price.denom;
-            fund_input = cw_utils::must_pay(&info, denom).unwrap();
-            price = launch.public_price.amount;
+        } else {
+            return Err(ContractError::Closed {});
+        }
 
-            // public
-            if fund_input.u128() < launch.public_price.amount.u128() {
-                return Err(ContractError::InsufficientFunds {});
-            }
+        // the taker keeps the whole anti-bot fee of free mints
+        let taker_fee = if price.is_zero() {
+            100
+        } else {
+            effective_taker_fee(deps.storage, &launch)?.0
+        };
+
+        // prepare calls
+        let mut messages = Vec::new();
+        let mut token_ids = Vec::new();
+        for number in tier_minted + 1..=tier_minted + quantity {
+            let (token_id, callback) = mint_next_token(
+                &env,
+                deps.storage,
+                &mut launch,
+                &contract_address,
+                &receiver_address,
+                tier.as_ref().map(|tier| (tier, number)),
+            )?;
+            token_ids.push(token_id);
+            messages.push(callback);
+        }
+        launch.sold += quantity;
+        if let Some(ref tier) = tier {
+            TIER_MINTED.save(
+                deps.storage,
+                (&contract_address, &tier.name),
+                &(tier_minted + quantity),
+            )?;
+        }
 
-            // check public quota
-            if let Some(public_max_buy) = launch.public_max_buy {
-                let public_items_key = format!("{}-{}", contract_address, "publicitems");
-                let public_items: Map<&Addr, u64> = Map::new(public_items_key.as_str());
+        LAUNCHES.save(deps.storage, &contract_address, &launch)?;
 
-                let previous_items = public_items
-                    .load(deps.storage, &receiver_address)
-                    .unwrap_or(0);
+        messages.extend(distribute_funds(
+            deps.storage,
+            &launch,
+            &contract_address,
+            &denom,
+            fund_input,
+            taker_fee,
+            referrer.as_ref(),
+            quantity,
+        )?);
 
-                if previous_items >= public_max_buy as u64 {
-                    return Err(ContractError::MintQuotaExhausted {});
-                }
-                public_items.save(deps.storage, &receiver_address, &(previous_items + 1))?;
-            }
-        } else {
-            return Err(ContractError::Closed {});
+        let mut response = Response::new()
+            .add_messages(messages)
+            .add_attribute("action", "mint_from_launchpad")
+            .add_attribute("contract_address", contract_address.to_string())
+            .add_attributes(token_ids.into_iter().map(|token_id| ("token_id", token_id)))
+            .add_attribute("receiver_address", receiver_address)
+            .add_attribute("price", price)
+            .add_attribute("quantity", quantity.to_string());
+        if let Some(referrer) = referrer {
+            response = response.add_attribute("referrer", referrer);
         }
 
-        // prepare call
-        let token_id = (launch.last_token_id + 1).to_string();
-        launch.last_token_id += 1;
+        Ok(response)
+    }
 
-        let token_uri = if launch.is_base_uri_static {
-            launch.base_uri.clone()
-        } else {
-            let media_extension = if let Some(media_extension) = launch.media_extension.clone() {
-                media_extension
-            } else {
-                "png".to_string()
-            };
+    pub fn receive_nft(
+        mut deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        receive_msg: Cw721ReceiveMsg,
+    ) -> Result<Response, ContractError> {
+        let ReceiveNftMsg::TradeIn {
+            contract_address,
+            receiver_address,
+            gate_token,
+        } = from_binary(&receive_msg.msg)?;
+        let contract_address = deps.api.addr_validate(&contract_address)?;
+        let mut launch = LAUNCHES.load(deps.storage, &contract_address)?;
 
-            format!("{}/{}.{}", launch.base_uri, token_id, media_extension)
-        };
+        if launch.raffle.is_some() {
+            return Err(ContractError::Closed {});
+        }
+        // tiered tokens are only sold through tier mints
+        if launch.tiers.is_some() {
+            return Err(ContractError::UnknownTier {});
+        }
 
-        let mint_msg = Cw721ExecuteMsg::<Option<Metadata>>::Mint {
-            token_id: token_id.clone(),
-            owner: receiver_address.to_string(),
-            token_uri: Some(token_uri),
-            extension: Some(Metadata {
-                royalty_percentage: launch.royalty_percentage.clone(),
-                royalty_payment_address: launch.royalty_payment_address.clone(),
-                image: None,
-                image_data: None,
-                external_url: None,
-                description: None,
-                name: None,
-                attributes: None,
-                background_color: None,
-                animation_url: None,
-                youtube_url: None,
-            }),
+        // only tokens of the configured collection are taken
+        let trade_in = match launch.trade_in {
+            Some(ref trade_in) if trade_in.collection == info.sender => trade_in.clone(),
+            _ => return Err(ContractError::TradeInNotAccepted {}),
         };
 
-        LAUNCHES.save(deps.storage, &contract_address, &launch)?;
+        if available_supply(&launch) == Some(0) {
+            return Err(ContractError::SoldOut {});
+        }
 
-        let callback =
-            Cw721Contract::<Empty, Empty>(contract_address.clone(), PhantomData, PhantomData)
-                .call(mint_msg)?;
+        let account_address = deps.api.addr_validate(&receive_msg.sender)?;
+        if let Some(ref mint_limits) = launch.mint_limits {
+            check_mint_limits(
+                deps.storage,
+                &deps.querier,
+                &env,
+                &contract_address,
+                &account_address,
+                mint_limits,
+                1,
+            )?;
+        }
 
-        let mut messages = Vec::new();
-        messages.push(callback);
+        let receiver_address = if let Some(receiver_id) = receiver_address {
+            deps.api.addr_validate(receiver_id.as_str())?
+        } else {
+            account_address.clone()
+        };
 
-        // fund transfers
-        // marketplace funds
-        let taker_fee = TAKERFEE.load(deps.storage)?;
-        let taker_funds = fund_input * Decimal::percent(taker_fee);
+        // the traded token stands in for the allowlist, the phase gates and price still apply
+        let phase =
+            current_phase(&launch, env.block.time.seconds()).ok_or(ContractError::Closed {})?;
+        let balance_max_buy = check_phase_gates(
+            deps.branch(),
+            &contract_address,
+            &launch,
+            &phase,
+            &account_address,
+            gate_token.as_deref(),
+            1,
+        )?;
+        let (price, phase_max_buy) = match phase {
+            Phase::Whitelist => (launch.whitelist_price.clone(), launch.whitelist_max_buy),
+            Phase::Public => (launch.public_price.clone(), launch.public_max_buy),
+        };
+        let due = match launch.free_mint_fee {
+            Some(ref free_mint_fee) if price.amount.is_zero() => free_mint_fee.clone(),
+            _ => price.clone(),
+        };
 
-        if taker_funds.u128() > 0 {
-            let send_taker_funds_msg = BankMsg::Send {
-                to_address: TAKERADDRESS.load(deps.storage).unwrap().to_string(),
-                amount: coins(taker_funds.u128(), denom),
-            };
-            messages.push(send_taker_funds_msg.into())
+        if !due.amount.is_zero() {
+            let key = (&contract_address, &account_address);
+            let deposit = TRADE_IN_DEPOSITS
+                .may_load(deps.storage, key)?
+                .unwrap_or_default();
+            if deposit < due.amount {
+                return Err(ContractError::InsufficientFunds {});
+            }
+            TRADE_IN_DEPOSITS.save(deps.storage, key, &(deposit - due.amount))?;
         }
 
-        // project owner funds
-        let owner_funds = fund_input - taker_funds;
-        if owner_funds.u128() > 0 {
-            let send_owner_funds_msg = BankMsg::Send {
-                to_address: launch.owner_address.to_string(),
-                amount: coins(owner_funds.u128(), denom),
-            };
+        let allowlist_entry = ALLOWLIST.may_load(
+            deps.storage,
+            (&contract_address, phase.as_str(), &account_address),
+        )?;
+        let max_buy = allowlist_entry
+            .and_then(|entry| entry.max_quantity)
+            .or(balance_max_buy)
+            .map(u64::from)
+            .or(phase_max_buy.map(u64::from));
+        if let Some(max_buy) = max_buy {
+            consume_phase_quota(
+                deps.storage,
+                &contract_address,
+                &phase,
+                &receiver_address,
+                &account_address,
+                1,
+                max_buy,
+            )?;
+        }
 
-            messages.push(send_owner_funds_msg.into())
+        let trade_in_msg = match trade_in.sink {
+            Some(ref sink) => cw721::Cw721ExecuteMsg::TransferNft {
+                recipient: sink.to_string(),
+                token_id: receive_msg.token_id.clone(),
+            },
+            None => cw721::Cw721ExecuteMsg::Burn {
+                token_id: receive_msg.token_id.clone(),
+            },
+        };
+        let mut messages = vec![WasmMsg::Execute {
+            contract_addr: trade_in.collection.to_string(),
+            msg: to_binary(&trade_in_msg)?,
+            funds: vec![],
         }
+        .into()];
+
+        let (token_id, callback) = mint_next_token(
+            &env,
+            deps.storage,
+            &mut launch,
+            &contract_address,
+            &receiver_address,
+            None,
+        )?;
+        messages.push(callback);
+        launch.sold += 1;
+
+        LAUNCHES.save(deps.storage, &contract_address, &launch)?;
+
+        let taker_fee = if price.amount.is_zero() {
+            100
+        } else {
+            effective_taker_fee(deps.storage, &launch)?.0
+        };
+        messages.extend(distribute_funds(
+            deps.storage,
+            &launch,
+            &contract_address,
+            &due.denom,
+            due.amount,
+            taker_fee,
+            None,
+            1,
+        )?);
 
         Ok(Response::new()
             .add_messages(messages)
-            .add_attribute("action", "mint_from_launchpad")
-            .add_attribute("contract_address", contract_address.to_string())
+            .add_attribute("action", "trade_in_mint")
+            .add_attribute("contract_address", contract_address)
+            .add_attribute("traded_token_id", receive_msg.token_id)
             .add_attribute("token_id", token_id)
             .add_attribute("receiver_address", receiver_address)
-            .add_attribute("price", price))
+            .add_attribute("price", due.amount))
     }
 
-    pub fn change_taker_fee(
+    pub fn deposit_trade_in_funds(
         deps: DepsMut,
         info: MessageInfo,
-        taker_fee: Uint64,
+        contract_address: String,
     ) -> Result<Response, ContractError> {
-        cw_ownable::assert_owner(deps.storage, &info.sender)?;
+        let contract_address = deps.api.addr_validate(&contract_address)?;
+        let launch = LAUNCHES.load(deps.storage, &contract_address)?;
+        if launch.trade_in.is_none() {
+            return Err(ContractError::TradeInNotAccepted {});
+        }
 
-        TAKERFEE.save(deps.storage, &taker_fee.u64())?;
+        let native_denom = NATIVE_DENOM.load(deps.storage)?;
+        let amount = cw_utils::must_pay(&info, &native_denom)?;
+        let deposit = TRADE_IN_DEPOSITS.update(
+            deps.storage,
+            (&contract_address, &info.sender),
+            |deposit| -> StdResult<_> { Ok(deposit.unwrap_or_default() + amount) },
+        )?;
 
         Ok(Response::new()
-            .add_attribute("action", "change_taker_fee")
-            .add_attribute("taker_fee", taker_fee))
+            .add_attribute("action", "deposit_trade_in_funds")
+            .add_attribute("contract_address", contract_address)
+            .add_attribute("depositor", info.sender)
+            .add_attribute("deposit", deposit))
     }
-}
 
-#[cfg_attr(not(feature = "library"), entry_point)]
-pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
-    match msg {
-        QueryMsg::GetLaunch { contract_address } => {
-            to_binary(&query::get_launch(deps, contract_address)?)
-        }
-        QueryMsg::GetLaunchStatus { contract_address } => {
-            to_binary(&query::get_launch_status(deps, env, contract_address)?)
+    pub fn withdraw_trade_in_funds(
+        deps: DepsMut,
+        info: MessageInfo,
+        contract_address: String,
+    ) -> Result<Response, ContractError> {
+        let contract_address = deps.api.addr_validate(&contract_address)?;
+        let key = (&contract_address, &info.sender);
+        let deposit = TRADE_IN_DEPOSITS
+            .may_load(deps.storage, key)?
+            .unwrap_or_default();
+
+        if deposit.is_zero() {
+            return Err(ContractError::NothingToWithdraw {});
         }
-        QueryMsg::GetWhitelistStatus {
-            contract_address,
-            account_address,
-            proof,
-        } => to_binary(&query::get_whitelist_status(
-            deps,
-            contract_address,
-            account_address,
-            proof,
-        )?),
+        TRADE_IN_DEPOSITS.remove(deps.storage, key);
+
+        let native_denom = NATIVE_DENOM.load(deps.storage)?;
+        let send_deposit_msg = BankMsg::Send {
+            to_address: info.sender.to_string(),
+            amount: coins(deposit.u128(), native_denom),
+        };
+
+        Ok(Response::new()
+            .add_message(send_deposit_msg)
+            .add_attribute("action", "withdraw_trade_in_funds")
+            .add_attribute("contract_address", contract_address)
+            .add_attribute("depositor", info.sender)
+            .add_attribute("amount", deposit))
     }
-}
 
-#[cfg_attr(not(feature = "library"), entry_point)]
-pub fn migrate(_deps: DepsMut, _env: Env, _msg: MigrateMsg) -> Result<Response, ContractError> {
-    Ok(Response::default())
-}
+    pub fn register(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        contract_address: String,
+    ) -> Result<Response, ContractError> {
+        let contract_address = deps.api.addr_validate(&contract_address)?;
+        let launch = LAUNCHES.load(deps.storage, &contract_address)?;
 
-pub mod query {
-    use crate::{
-        msg::{LaunchStatus, WhitelistStatus},
-        state::{Launch, LAUNCHES},
-    };
+        let raffle = launch
+            .raffle
+            .as_ref()
+            .ok_or(ContractError::InvalidRaffle {})?;
+        let now = env.block.time.seconds();
+        if now < raffle.registration_started_at || now >= raffle.registration_ended_at {
+            return Err(ContractError::RaffleClosed {});
+        }
 
-    use super::*;
+        let key = (&contract_address, &info.sender);
+        if RAFFLE_ENTRIES.has(deps.storage, key) {
+            return Err(ContractError::AlreadyRegistered {});
+        }
+        let deposit = collect_payment(
+            &info,
+            &launch,
+            &launch.public_price.denom,
+            launch.public_price.amount,
+            1,
+        )?;
 
-    pub fn get_launch(deps: Deps, contract_address: String) -> StdResult<Launch> {
-        let launch = LAUNCHES.load(deps.storage, &deps.api.addr_validate(&contract_address)?)?;
+        let mut state = RAFFLES
+            .may_load(deps.storage, &contract_address)?
+            .unwrap_or_default();
+        RAFFLE_REGISTRANTS.save(
+            deps.storage,
+            (&contract_address, state.registrants),
+            &info.sender,
+        )?;
+        RAFFLE_ENTRIES.save(
+            deps.storage,
+            key,
+            &RaffleEntry {
+                index: state.registrants,
+                deposit,
+            },
+        )?;
+        state.registrants += 1;
+        RAFFLES.save(deps.storage, &contract_address, &state)?;
 
-        Ok(launch)
+        Ok(Response::new()
+            .add_attribute("action", "register")
+            .add_attribute("contract_address", contract_address)
+            .add_attribute("registrant", info.sender)
+            .add_attribute("deposit", deposit))
     }
 
-    pub fn get_launch_status(
-        deps: Deps,
+    pub fn draw_raffle(
+        deps: DepsMut,
         env: Env,
+        info: MessageInfo,
         contract_address: String,
-    ) -> StdResult<LaunchStatus> {
-        let launch = LAUNCHES.load(deps.storage, &deps.api.addr_validate(&contract_address)?)?;
+        seed: Option<String>,
+    ) -> Result<Response, ContractError> {
+        let contract_address = deps.api.addr_validate(&contract_address)?;
+        let launch = LAUNCHES.load(deps.storage, &contract_address)?;
 
-        let current_timestamp_in_seconds = env.block.time.seconds();
-        if current_timestamp_in_seconds > launch.whitelist_started_at
-            && current_timestamp_in_seconds < launch.whitelist_ended_at
-        {
-            Ok(LaunchStatus {
-                status: "whitelist".to_string(),
-            })
-        } else if current_timestamp_in_seconds > launch.public_started_at
-            && current_timestamp_in_seconds < launch.public_ended_at
-        {
-            Ok(LaunchStatus {
-                status: "public".to_string(),
-            })
-        } else {
-            Ok(LaunchStatus {
-                status: "closed".to_string(),
-            })
+        assert_launch_owner_or_admin(deps.storage, &info.sender, &launch)?;
+
+        let raffle = launch
+            .raffle
+            .as_ref()
+            .ok_or(ContractError::InvalidRaffle {})?;
+        let now = env.block.time.seconds();
+        if now < raffle.registration_ended_at {
+            return Err(ContractError::RaffleClosed {});
+        }
+        if now >= raffle.draw_deadline {
+            return Err(ContractError::RaffleExpired {});
+        }
+        let mut state = RAFFLES
+            .may_load(deps.storage, &contract_address)?
+            .unwrap_or_default();
+        if state.seed.is_some() {
+            return Err(ContractError::RaffleAlreadyDrawn {});
         }
+        if state.pending_job.is_some() {
+            return Err(ContractError::RandomnessPending {});
+        }
+
+        let seed = match (&raffle.seed_commitment, seed) {
+            (Some(seed_commitment), Some(seed)) => {
+                if sha2::Sha256::digest(seed.as_bytes()).as_slice() != parse_hash(seed_commitment)?
+                {
+                    return Err(ContractError::InvalidSeed {});
+                }
+                seed
+            }
+            // the draw continues once the beacon calls back
+            (None, None) => {
+                let (job_id, request_msg) = request_randomness(
+                    deps.storage,
+                    &RandomnessJob::DrawRaffle {
+                        contract_address: contract_address.clone(),
+                    },
+                    info.funds,
+                )?;
+                state.pending_job = Some(job_id.clone());
+                RAFFLES.save(deps.storage, &contract_address, &state)?;
+
+                return Ok(Response::new()
+                    .add_message(request_msg)
+                    .add_attribute("action", "request_raffle_randomness")
+                    .add_attribute("contract_address", contract_address)
+                    .add_attribute("job_id", job_id));
+            }
+            _ => return Err(ContractError::InvalidSeed {}),
+        };
+
+        shuffle_raffle(deps.storage, &contract_address, &launch, &mut state, seed)?;
+
+        Ok(Response::new()
+            .add_attribute("action", "draw_raffle")
+            .add_attribute("contract_address", contract_address)
+            .add_attribute("registrants", state.registrants.to_string())
+            .add_attribute("winners", state.winners.to_string()))
     }
 
-    pub fn get_whitelist_status(
-        deps: Deps,
+    pub fn cancel_raffle_draw(
+        deps: DepsMut,
+        info: MessageInfo,
         contract_address: String,
-        account_address: String,
-        proof: Vec<String>,
-    ) -> StdResult<WhitelistStatus> {
-        // verify merkle root
-        let launch = get_launch(deps, contract_address).unwrap();
+    ) -> Result<Response, ContractError> {
+        let contract_address = deps.api.addr_validate(&contract_address)?;
+        let launch = LAUNCHES.load(deps.storage, &contract_address)?;
 
-        let merkle_root = launch.whitelist_merkle_root.unwrap();
+        assert_launch_owner_or_admin(deps.storage, &info.sender, &launch)?;
 
-        if !verify_merkle_proof(account_address, &merkle_root, proof) {
-            Ok(WhitelistStatus {
-                is_whitelist: false,
-            })
-        } else {
-            Ok(WhitelistStatus { is_whitelist: true })
-        }
+        let mut state = RAFFLES
+            .may_load(deps.storage, &contract_address)?
+            .unwrap_or_default();
+        let job_id = state
+            .pending_job
+            .take()
+            .ok_or(ContractError::RandomnessNotPending {})?;
+        randomness::cancel_randomness(deps.storage, &job_id);
+        RAFFLES.save(deps.storage, &contract_address, &state)?;
+
+        Ok(Response::new()
+            .add_attribute("action", "cancel_raffle_draw")
+            .add_attribute("contract_address", contract_address)
+            .add_attribute("job_id", job_id))
     }
-}
 
-fn verify_merkle_proof(user_input: String, merkle_root: &String, proof: Vec<String>) -> bool {
-    // https://github.com/CosmWasm/cw-tokens/blob/main/contracts/cw20-merkle-airdrop/src/contract.rs#L282
-    let hash = sha2::Sha256::digest(user_input.as_bytes())
-        .as_slice()
-        .try_into()
-        .unwrap();
+    pub fn receive_randomness(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        callback: RandomnessCallback,
+    ) -> Result<Response, ContractError> {
+        let (job, randomness) =
+            randomness::receive_randomness(deps.storage, &info.sender, &callback)?;
 
-    let hash = proof
-        .into_iter()
-        .try_fold(hash, |hash, p| {
-            let mut proof_buf = [0; 32];
-            let _ = hex::decode_to_slice(p, &mut proof_buf);
-            let mut hashes = [hash, proof_buf];
-            hashes.sort_unstable();
-            sha2::Sha256::digest(&hashes.concat()).as_slice().try_into()
-        })
-        .unwrap();
+        match job {
+            RandomnessJob::DrawRaffle { contract_address } => {
+                let launch = LAUNCHES.load(deps.storage, &contract_address)?;
+                let mut state = RAFFLES.load(deps.storage, &contract_address)?;
+                state.pending_job = None;
 
-    let mut root_buf: [u8; 32] = [0; 32];
-    let _ = hex::decode_to_slice(merkle_root, &mut root_buf);
-    return root_buf == hash;
-}
+                // registrants may already have withdrawn, the late randomness is dropped
+                if launch
+                    .raffle
+                    .as_ref()
+                    .is_some_and(|raffle| env.block.time.seconds() >= raffle.draw_deadline)
+                {
+                    RAFFLES.save(deps.storage, &contract_address, &state)?;
+                    return Ok(Response::new()
+                        .add_attribute("action", "expire_raffle_randomness")
+                        .add_attribute("contract_address", contract_address)
+                        .add_attribute("job_id", callback.job_id));
+                }
+                shuffle_raffle(
+                    deps.storage,
+                    &contract_address,
+                    &launch,
+                    &mut state,
+                    hex::encode(randomness),
+                )?;
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info};
-    use cosmwasm_std::{coins, Uint64};
+                Ok(Response::new()
+                    .add_attribute("action", "draw_raffle")
+                    .add_attribute("contract_address", contract_address)
+                    .add_attribute("job_id", callback.job_id)
+                    .add_attribute("registrants", state.registrants.to_string())
+                    .add_attribute("winners", state.winners.to_string()))
+            }
+        }
+    }
 
-    #[test]
-    fn proper_initialization() {
-        let mut deps = mock_dependencies();
+    // picks the winners of a raffle from the seed and stores the result
+    fn shuffle_raffle(
+        storage: &mut dyn Storage,
+        contract_address: &Addr,
+        launch: &Launch,
+        state: &mut RaffleState,
+        seed: String,
+    ) -> Result<(), ContractError> {
+        let raffle = launch
+            .raffle
+            .as_ref()
+            .ok_or(ContractError::InvalidRaffle {})?;
 
-        let msg = InstantiateMsg {
-            taker_fee: Uint64::new(10),
-            native_denom: "aconst".to_string(),
-            taker_address: "admin".to_string(),
-        };
-        let info = mock_info("creator", &coins(1000, "earth"));
+        let winners = u64::from(raffle.winners)
+            .min(u64::from(state.registrants))
+            .min(available_supply(launch).unwrap_or(u64::MAX)) as u32;
 
-        // we can just call .unwrap() to assert this was a success
-        let res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
-        assert_eq!(0, res.messages.len());
+        // partial fisher-yates shuffle, the first `winners` positions win
+        for position in 0..winners {
+            let swap_with = position
+                + (raffle_random(&seed, position) % u64::from(state.registrants - position)) as u32;
+            if swap_with != position {
+                let first = RAFFLE_REGISTRANTS.load(storage, (contract_address, position))?;
+                let second = RAFFLE_REGISTRANTS.load(storage, (contract_address, swap_with))?;
+                for (index, registrant) in [(position, &second), (swap_with, &first)] {
+                    RAFFLE_REGISTRANTS.save(storage, (contract_address, index), registrant)?;
+                    RAFFLE_ENTRIES.update(
+                        storage,
+                        (contract_address, registrant),
+                        |entry| -> StdResult<_> {
+                            let mut entry =
+                                entry.ok_or_else(|| StdError::not_found("RaffleEntry"))?;
+                            entry.index = index;
+                            Ok(entry)
+                        },
+                    )?;
+                }
+            }
+        }
+
+        state.winners = winners;
+        state.seed = Some(seed);
+        RAFFLES.save(storage, contract_address, state)?;
+
+        Ok(())
+    }
+
+    pub fn set_randomness_beacon(
+        deps: DepsMut,
+        info: MessageInfo,
+        beacon: Option<String>,
+    ) -> Result<Response, ContractError> {
+        cw_ownable::assert_owner(deps.storage, &info.sender)?;
+
+        match beacon {
+            Some(ref beacon) => {
+                RANDOMNESS_BEACON.save(deps.storage, &deps.api.addr_validate(beacon)?)?
+            }
+            None => RANDOMNESS_BEACON.remove(deps.storage),
+        }
+
+        Ok(Response::new()
+            .add_attribute("action", "set_randomness_beacon")
+            .add_attribute("beacon", beacon.unwrap_or_default()))
+    }
+
+    pub fn claim_raffle_mint(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        contract_address: String,
+    ) -> Result<Response, ContractError> {
+        let contract_address = deps.api.addr_validate(&contract_address)?;
+        let mut launch = LAUNCHES.load(deps.storage, &contract_address)?;
+        if launch.tiers.is_some() {
+            return Err(ContractError::UnknownTier {});
+        }
+
+        let (entry, won) = raffle_result(deps.storage, &env, &contract_address, &info.sender)?;
+        if !won {
+            return Err(ContractError::NotRaffleWinner {});
+        }
+        RAFFLE_ENTRIES.remove(deps.storage, (&contract_address, &info.sender));
+
+        let (token_id, callback) = mint_next_token(
+            &env,
+            deps.storage,
+            &mut launch,
+            &contract_address,
+            &info.sender,
+            None,
+        )?;
+        launch.sold += 1;
+        LAUNCHES.save(deps.storage, &contract_address, &launch)?;
+
+        let taker_fee = if launch.public_price.amount.is_zero() {
+            100
+        } else {
+            effective_taker_fee(deps.storage, &launch)?.0
+        };
+        let native_denom = NATIVE_DENOM.load(deps.storage)?;
+        let mut messages = vec![callback];
+        messages.extend(distribute_funds(
+            deps.storage,
+            &launch,
+            &contract_address,
+            &native_denom,
+            entry.deposit,
+            taker_fee,
+            None,
+            1,
+        )?);
+
+        Ok(Response::new()
+            .add_messages(messages)
+            .add_attribute("action", "claim_raffle_mint")
+            .add_attribute("contract_address", contract_address)
+            .add_attribute("token_id", token_id)
+            .add_attribute("receiver_address", info.sender))
+    }
+
+    pub fn withdraw_raffle_deposit(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        contract_address: String,
+    ) -> Result<Response, ContractError> {
+        let contract_address = deps.api.addr_validate(&contract_address)?;
+
+        // winners pay with their deposit when claiming
+        let (entry, won) = raffle_result(deps.storage, &env, &contract_address, &info.sender)?;
+        if won || entry.deposit.is_zero() {
+            return Err(ContractError::NothingToWithdraw {});
+        }
+        RAFFLE_ENTRIES.remove(deps.storage, (&contract_address, &info.sender));
+
+        let native_denom = NATIVE_DENOM.load(deps.storage)?;
+        let send_deposit_msg = BankMsg::Send {
+            to_address: info.sender.to_string(),
+            amount: coins(entry.deposit.u128(), native_denom),
+        };
+
+        Ok(Response::new()
+            .add_message(send_deposit_msg)
+            .add_attribute("action", "withdraw_raffle_deposit")
+            .add_attribute("contract_address", contract_address)
+            .add_attribute("registrant", info.sender)
+            .add_attribute("amount", entry.deposit))
+    }
+
+    fn raffle_result(
+        storage: &dyn Storage,
+        env: &Env,
+        contract_address: &Addr,
+        registrant: &Addr,
+    ) -> Result<(RaffleEntry, bool), ContractError> {
+        let state = RAFFLES
+            .may_load(storage, contract_address)?
+            .unwrap_or_default();
+        if state.seed.is_none() {
+            // nobody won a raffle left undrawn past its deadline
+            let expired = LAUNCHES
+                .may_load(storage, contract_address)?
+                .and_then(|launch| launch.raffle)
+                .is_some_and(|raffle| env.block.time.seconds() >= raffle.draw_deadline);
+            if !expired {
+                return Err(ContractError::RaffleNotDrawn {});
+            }
+        }
+        let entry = RAFFLE_ENTRIES
+            .may_load(storage, (contract_address, registrant))?
+            .ok_or(ContractError::NotRegistered {})?;
+        let won = entry.index < state.winners;
+
+        Ok((entry, won))
+    }
+
+    pub fn withdraw_referral_rewards(
+        deps: DepsMut,
+        info: MessageInfo,
+    ) -> Result<Response, ContractError> {
+        let pending = REFERRAL_REWARDS
+            .may_load(deps.storage, &info.sender)?
+            .unwrap_or_default();
+
+        if pending.is_zero() {
+            return Err(ContractError::NothingToWithdraw {});
+        }
+        REFERRAL_REWARDS.remove(deps.storage, &info.sender);
+
+        let native_denom = NATIVE_DENOM.load(deps.storage)?;
+        let send_rewards_msg = BankMsg::Send {
+            to_address: info.sender.to_string(),
+            amount: coins(pending.u128(), native_denom),
+        };
+
+        Ok(Response::new()
+            .add_message(send_rewards_msg)
+            .add_attribute("action", "withdraw_referral_rewards")
+            .add_attribute("referrer", info.sender)
+            .add_attribute("amount", pending))
+    }
+
+    pub fn set_starting_index(
+        deps: DepsMut,
+        env: Env,
+        contract_address: String,
+    ) -> Result<Response, ContractError> {
+        let contract_address = deps.api.addr_validate(&contract_address)?;
+        let mut launch = LAUNCHES.load(deps.storage, &contract_address)?;
+
+        // an open edition gets its starting index from FinalizeLaunch
+        if launch.max_supply.is_none() {
+            return Err(ContractError::InvalidOpenEdition {});
+        }
+        if launch.provenance_hash.is_none() {
+            return Err(ContractError::ProvenanceNotSet {});
+        }
+        if launch.starting_index.is_some() {
+            return Err(ContractError::StartingIndexSet {});
+        }
+        let sale_ended_at = launch.whitelist_ended_at.max(launch.public_ended_at);
+        if env.block.time.seconds() < sale_ended_at {
+            return Err(ContractError::SaleNotEnded {});
+        }
+
+        let starting_index = starting_index(&env, &contract_address, &launch)?;
+        launch.starting_index = Some(starting_index);
+        LAUNCHES.save(deps.storage, &contract_address, &launch)?;
+
+        Ok(Response::new()
+            .add_attribute("action", "set_starting_index")
+            .add_attribute("contract_address", contract_address)
+            .add_attribute("starting_index", starting_index.to_string()))
+    }
+
+    pub fn add_token_ids(
+        deps: DepsMut,
+        info: MessageInfo,
+        contract_address: String,
+        token_ids: Vec<String>,
+    ) -> Result<Response, ContractError> {
+        let contract_address = deps.api.addr_validate(&contract_address)?;
+        let launch = LAUNCHES.load(deps.storage, &contract_address)?;
+        assert_launch_owner_or_admin(deps.storage, &info.sender, &launch)?;
+
+        if launch.token_id_scheme != Some(TokenIdScheme::List {}) {
+            return Err(ContractError::InvalidTokenIdScheme {});
+        }
+
+        let mut count = TOKEN_ID_COUNT
+            .may_load(deps.storage, &contract_address)?
+            .unwrap_or_default();
+        for token_id in &token_ids {
+            if token_id.is_empty() {
+                return Err(ContractError::InvalidTokenIdScheme {});
+            }
+            if TOKEN_ID_POSITIONS.has(deps.storage, (&contract_address, token_id)) {
+                return Err(ContractError::DuplicateTokenId {});
+            }
+            count += 1;
+            TOKEN_ID_POSITIONS.save(deps.storage, (&contract_address, token_id), &count)?;
+            TOKEN_IDS.save(deps.storage, (&contract_address, count), token_id)?;
+        }
+        TOKEN_ID_COUNT.save(deps.storage, &contract_address, &count)?;
+
+        Ok(Response::new()
+            .add_attribute("action", "add_token_ids")
+            .add_attribute("contract_address", contract_address)
+            .add_attribute("count", token_ids.len().to_string())
+            .add_attribute("total", count.to_string()))
+    }
+
+    pub fn add_to_allowlist(
+        deps: DepsMut,
+        info: MessageInfo,
+        contract_address: String,
+        phase: Phase,
+        entries: Vec<AllowlistEntryInfo>,
+    ) -> Result<Response, ContractError> {
+        let contract_address = deps.api.addr_validate(&contract_address)?;
+        let launch = LAUNCHES.load(deps.storage, &contract_address)?;
+        assert_launch_owner_or_admin(deps.storage, &info.sender, &launch)?;
+
+        let count = entries.len();
+        for entry in entries {
+            let address = deps.api.addr_validate(&entry.address)?;
+            ALLOWLIST.save(
+                deps.storage,
+                (&contract_address, phase.as_str(), &address),
+                &AllowlistEntry {
+                    max_quantity: entry.max_quantity,
+                },
+            )?;
+        }
+
+        Ok(Response::new()
+            .add_attribute("action", "add_to_allowlist")
+            .add_attribute("contract_address", contract_address)
+            .add_attribute("phase", phase.as_str())
+            .add_attribute("count", count.to_string()))
+    }
+
+    pub fn remove_from_allowlist(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        contract_address: String,
+        phase: Phase,
+        addresses: Vec<String>,
+    ) -> Result<Response, ContractError> {
+        let contract_address = deps.api.addr_validate(&contract_address)?;
+        let launch = LAUNCHES.load(deps.storage, &contract_address)?;
+        assert_launch_owner_or_admin(deps.storage, &info.sender, &launch)?;
+
+        let count = addresses.len();
+        for address in addresses {
+            let address = deps.api.addr_validate(&address)?;
+            ALLOWLIST.remove(deps.storage, (&contract_address, phase.as_str(), &address));
+        }
+        validate_whitelist_source(
+            deps.storage,
+            &contract_address,
+            &launch,
+            env.block.time.seconds(),
+        )?;
+
+        Ok(Response::new()
+            .add_attribute("action", "remove_from_allowlist")
+            .add_attribute("contract_address", contract_address)
+            .add_attribute("phase", phase.as_str())
+            .add_attribute("count", count.to_string()))
+    }
+
+    pub fn airdrop_mint(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        contract_address: String,
+        recipients: Vec<(String, u32)>,
+    ) -> Result<Response, ContractError> {
+        let contract_address = deps.api.addr_validate(&contract_address)?;
+        let mut launch = LAUNCHES.load(deps.storage, &contract_address)?;
+
+        assert_launch_owner_or_admin(deps.storage, &info.sender, &launch)?;
+        if launch.tiers.is_some() {
+            return Err(ContractError::UnknownTier {});
+        }
+
+        let total: u64 = recipients
+            .iter()
+            .map(|(_, quantity)| *quantity as u64)
+            .sum();
+        if total == 0 {
+            return Err(ContractError::EmptyAirdrop {});
+        }
+        if total > reserved_remaining(&launch) {
+            return Err(ContractError::ReservedSupplyExhausted {});
+        }
+
+        let mut messages = Vec::new();
+        for (recipient, quantity) in recipients {
+            let recipient = deps.api.addr_validate(&recipient)?;
+            for _ in 0..quantity {
+                let (_, callback) = mint_next_token(
+                    &env,
+                    deps.storage,
+                    &mut launch,
+                    &contract_address,
+                    &recipient,
+                    None,
+                )?;
+                messages.push(callback);
+            }
+        }
+        launch.reserved_minted += total;
+
+        LAUNCHES.save(deps.storage, &contract_address, &launch)?;
+
+        Ok(Response::new()
+            .add_messages(messages)
+            .add_attribute("action", "airdrop_mint")
+            .add_attribute("contract_address", contract_address)
+            .add_attribute("minted", total.to_string())
+            .add_attribute("last_token_id", launch.last_token_id.to_string()))
+    }
+
+    // splits a mint payment between the taker, the referrer and the launch owner
+    fn distribute_funds(
+        storage: &mut dyn Storage,
+        launch: &Launch,
+        contract_address: &Addr,
+        denom: &str,
+        fund_input: Uint128,
+        taker_fee: u64,
+        referrer: Option<&Addr>,
+        quantity: u64,
+    ) -> StdResult<Vec<CosmosMsg>> {
+        let mut messages = Vec::new();
+
+        // fund transfers
+        // marketplace funds
+        let taker_funds = fund_input * Decimal::percent(taker_fee);
+
+        if taker_funds.u128() > 0 {
+            let send_taker_funds_msg = BankMsg::Send {
+                to_address: TAKERADDRESS.load(storage)?.to_string(),
+                amount: coins(taker_funds.u128(), denom),
+            };
+            messages.push(send_taker_funds_msg.into())
+        }
+
+        // project owner funds, less the referrer share
+        let mut owner_funds = fund_input - taker_funds;
+
+        if let Some(referrer) = referrer {
+            let referral_funds = match launch.referral {
+                Some(ref referral) => owner_funds * Decimal::percent(referral.share_percentage),
+                None => Uint128::zero(),
+            };
+            owner_funds -= referral_funds;
+
+            if referral_funds.u128() > 0 {
+                if matches!(launch.referral, Some(ReferralConfig { accrue: true, .. })) {
+                    REFERRAL_REWARDS.update(storage, referrer, |pending| -> StdResult<_> {
+                        Ok(pending.unwrap_or_default() + referral_funds)
+                    })?;
+                } else {
+                    let send_referral_funds_msg = BankMsg::Send {
+                        to_address: referrer.to_string(),
+                        amount: coins(referral_funds.u128(), denom),
+                    };
+                    messages.push(send_referral_funds_msg.into())
+                }
+            }
+
+            REFERRAL_TOTALS.update(
+                storage,
+                (contract_address, referrer),
+                |total| -> StdResult<_> {
+                    let mut total = total.unwrap_or_default();
+                    total.minted += quantity;
+                    total.earned += referral_funds;
+                    Ok(total)
+                },
+            )?;
+        }
+
+        if owner_funds.u128() > 0 {
+            let send_owner_funds_msg = BankMsg::Send {
+                to_address: launch.owner_address.to_string(),
+                amount: coins(owner_funds.u128(), denom),
+            };
+
+            messages.push(send_owner_funds_msg.into())
+        }
+
+        Ok(messages)
+    }
+
+    // takes the next token id of the launch and builds the cw721 mint for it
+    fn mint_next_token(
+        env: &Env,
+        storage: &dyn Storage,
+        launch: &mut Launch,
+        contract_address: &Addr,
+        owner: &Addr,
+        tier: Option<(&Tier, u64)>,
+    ) -> Result<(String, CosmosMsg), ContractError> {
+        if launch.edition_size.is_some() {
+            return Err(ContractError::LaunchFinalized {});
+        }
+
+        let number = tier.map_or(launch.last_token_id + 1, |(_, number)| number);
+        let token_id = token_id(
+            storage,
+            launch,
+            contract_address,
+            tier.map(|(tier, _)| tier),
+            number,
+        )?;
+        let token_uri = token_uri(
+            launch,
+            contract_address,
+            tier.map(|(tier, _)| tier),
+            number,
+            &token_id,
+        )?;
+        launch.last_token_id += 1;
+        if launch.locked_at.is_none() {
+            launch.locked_at = Some(env.block.time.seconds());
+        }
+        let sold_out = launch.max_supply == Some(launch.last_token_id);
+        if sold_out && launch.provenance_hash.is_some() && launch.starting_index.is_none() {
+            launch.starting_index = Some(starting_index(env, contract_address, launch)?);
+        }
+
+        let mint_msg = Cw721ExecuteMsg::<Option<Metadata>>::Mint {
+            token_id: token_id.clone(),
+            owner: owner.to_string(),
+            token_uri: Some(token_uri),
+            extension: Some(Metadata {
+                royalty_percentage: launch.royalty_percentage,
+                royalty_payment_address: launch.royalty_payment_address.clone(),
+                image: None,
+                image_data: None,
+                external_url: None,
+                description: None,
+                name: None,
+                attributes: None,
+                background_color: None,
+                animation_url: None,
+                youtube_url: None,
+            }),
+        };
+
+        let callback =
+            Cw721Contract::<Empty, Empty>(contract_address.clone(), PhantomData, PhantomData)
+                .call(mint_msg)?;
+
+        Ok((token_id, callback))
+    }
+
+    pub fn change_taker_fee(
+        deps: DepsMut,
+        info: MessageInfo,
+        taker_fee: Uint64,
+    ) -> Result<Response, ContractError> {
+        cw_ownable::assert_owner(deps.storage, &info.sender)?;
+
+        TAKERFEE.save(deps.storage, &taker_fee.u64())?;
+
+        Ok(Response::new()
+            .add_attribute("action", "change_taker_fee")
+            .add_attribute("taker_fee", taker_fee))
+    }
+
+    pub fn change_launch_taker_fee(
+        deps: DepsMut,
+        info: MessageInfo,
+        contract_address: String,
+        taker_fee: Update<u64>,
+        taker_fee_tiers: Update<Vec<FeeTier>>,
+    ) -> Result<Response, ContractError> {
+        cw_ownable::assert_owner(deps.storage, &info.sender)?;
+        let contract_address = deps.api.addr_validate(&contract_address)?;
+
+        let mut launch = LAUNCHES.load(deps.storage, &contract_address)?;
+
+        if let Update::Set(taker_fee) = taker_fee {
+            if taker_fee > 100 {
+                return Err(ContractError::InvalidTakerFee {});
+            }
+        }
+
+        if let Update::Set(ref tiers) = taker_fee_tiers {
+            // tiers must be sorted by strictly increasing min_sold
+            let sorted = tiers.windows(2).all(|w| w[0].min_sold < w[1].min_sold);
+            if !sorted || tiers.iter().any(|tier| tier.taker_fee > 100) {
+                return Err(ContractError::InvalidFeeTiers {});
+            }
+        }
+
+        taker_fee.apply(&mut launch.taker_fee);
+        taker_fee_tiers.apply(&mut launch.taker_fee_tiers);
+
+        LAUNCHES.save(deps.storage, &contract_address, &launch)?;
+
+        let (effective_taker_fee, _) = effective_taker_fee(deps.storage, &launch)?;
+
+        Ok(Response::new()
+            .add_attribute("action", "change_launch_taker_fee")
+            .add_attribute("contract_address", contract_address)
+            .add_attribute("effective_taker_fee", effective_taker_fee.to_string()))
+    }
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
+    match msg {
+        QueryMsg::GetLaunch { contract_address } => {
+            to_binary(&query::get_launch(deps, contract_address)?)
+        }
+        QueryMsg::GetLaunchStatus { contract_address } => {
+            to_binary(&query::get_launch_status(deps, env, contract_address)?)
+        }
+        QueryMsg::PreviewTokenUri {
+            contract_address,
+            number,
+            tier,
+        } => to_binary(&query::preview_token_uri(
+            deps,
+            contract_address,
+            number,
+            tier,
+        )?),
+        QueryMsg::GetWhitelistStatus {
+            contract_address,
+            account_address,
+            proof,
+            allocation,
+        } => to_binary(&query::get_whitelist_status(
+            deps,
+            contract_address,
+            account_address,
+            proof,
+            allocation,
+        )?),
+        QueryMsg::GetReferrals {
+            contract_address,
+            start_after,
+            limit,
+        } => to_binary(&query::get_referrals(
+            deps,
+            contract_address,
+            start_after,
+            limit,
+        )?),
+        QueryMsg::GetReferralRewards { address } => {
+            to_binary(&query::get_referral_rewards(deps, address)?)
+        }
+        QueryMsg::GetAllowlist {
+            contract_address,
+            phase,
+            start_after,
+            limit,
+        } => to_binary(&query::get_allowlist(
+            deps,
+            contract_address,
+            phase,
+            start_after,
+            limit,
+        )?),
+        QueryMsg::GetLaunchOwnership { contract_address } => {
+            to_binary(&query::get_launch_ownership(deps, contract_address)?)
+        }
+        QueryMsg::GetArchivedLaunch { contract_address } => {
+            to_binary(&query::get_archived_launch(deps, contract_address)?)
+        }
+        QueryMsg::GetArchivedLaunches { start_after, limit } => {
+            to_binary(&query::get_archived_launches(deps, start_after, limit)?)
+        }
+        QueryMsg::GetRaffle {
+            contract_address,
+            address,
+        } => to_binary(&query::get_raffle(deps, contract_address, address)?),
+        QueryMsg::GetProvenance { contract_address } => {
+            to_binary(&query::get_provenance(deps, contract_address)?)
+        }
+    }
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn migrate(_deps: DepsMut, _env: Env, _msg: MigrateMsg) -> Result<Response, ContractError> {
+    Ok(Response::default())
+}
+
+pub mod query {
+    use cosmwasm_std::{Coin, Uint64};
+    use cw_storage_plus::Bound;
+
+    use crate::{
+        msg::{
+            AllowlistResponse, ArchivedLaunchInfo, ArchivedLaunchesResponse,
+            LaunchOwnershipResponse, LaunchResponse, LaunchStatus, ProvenanceResponse,
+            RaffleEntryInfo, RaffleResponse, ReferralInfo, ReferralRewardsResponse,
+            ReferralsResponse, TierStatus, TokenUriResponse, WhitelistStatus,
+        },
+        state::{
+            ArchivedLaunch, ARCHIVED_LAUNCHES, LAUNCHES, PENDING_LAUNCH_OWNERS, RAFFLE_ENTRIES,
+            REFERRAL_REWARDS, REFERRAL_TOTALS,
+        },
+    };
+
+    use super::*;
+
+    const DEFAULT_LIMIT: u32 = 10;
+    const MAX_LIMIT: u32 = 30;
+
+    pub fn get_launch(deps: Deps, contract_address: String) -> StdResult<LaunchResponse> {
+        let launch = LAUNCHES.load(deps.storage, &deps.api.addr_validate(&contract_address)?)?;
+        let (effective_taker_fee, taker_fee_tier) = effective_taker_fee(deps.storage, &launch)?;
+
+        Ok(LaunchResponse {
+            is_locked: launch.locked_at.is_some(),
+            launch,
+            effective_taker_fee: Uint64::new(effective_taker_fee),
+            taker_fee_tier,
+        })
+    }
+
+    pub fn get_raffle(
+        deps: Deps,
+        contract_address: String,
+        address: Option<String>,
+    ) -> StdResult<RaffleResponse> {
+        let contract_address = deps.api.addr_validate(&contract_address)?;
+        let state = RAFFLES
+            .may_load(deps.storage, &contract_address)?
+            .unwrap_or_default();
+        let drawn = state.seed.is_some();
+
+        let entry = match address {
+            Some(address) => RAFFLE_ENTRIES
+                .may_load(
+                    deps.storage,
+                    (&contract_address, &deps.api.addr_validate(&address)?),
+                )?
+                .map(|entry| RaffleEntryInfo {
+                    deposit: entry.deposit,
+                    won: drawn.then_some(entry.index < state.winners),
+                }),
+            None => None,
+        };
+
+        Ok(RaffleResponse {
+            registrants: state.registrants,
+            winners: state.winners,
+            drawn,
+            entry,
+        })
+    }
+
+    pub fn get_launch_ownership(
+        deps: Deps,
+        contract_address: String,
+    ) -> StdResult<LaunchOwnershipResponse> {
+        let contract_address = deps.api.addr_validate(&contract_address)?;
+        let launch = LAUNCHES.load(deps.storage, &contract_address)?;
+        let pending = PENDING_LAUNCH_OWNERS.may_load(deps.storage, &contract_address)?;
+
+        Ok(LaunchOwnershipResponse {
+            owner: launch.owner_address,
+            pending_owner: pending.as_ref().map(|pending| pending.new_owner.clone()),
+            pending_expiry: pending.and_then(|pending| pending.expiry),
+        })
+    }
+
+    pub fn preview_token_uri(
+        deps: Deps,
+        contract_address: String,
+        number: u64,
+        tier: Option<String>,
+    ) -> StdResult<TokenUriResponse> {
+        let contract_address = deps.api.addr_validate(&contract_address)?;
+        let launch = LAUNCHES.load(deps.storage, &contract_address)?;
+
+        // tokens are numbered from 1
+        if number == 0 {
+            return Err(StdError::generic_err(
+                ContractError::InvalidQuantity {}.to_string(),
+            ));
+        }
+        let tier = match tier {
+            Some(tier) => Some(
+                launch
+                    .tiers
+                    .iter()
+                    .flatten()
+                    .find(|candidate| candidate.name == tier)
+                    .ok_or_else(|| {
+                        StdError::generic_err(ContractError::UnknownTier {}.to_string())
+                    })?,
+            ),
+            None => None,
+        };
+        let token_id = token_id(deps.storage, &launch, &contract_address, tier, number)
+            .map_err(|err| StdError::generic_err(err.to_string()))?;
+        let token_uri = token_uri(&launch, &contract_address, tier, number, &token_id)
+            .map_err(|err| StdError::generic_err(err.to_string()))?;
+
+        Ok(TokenUriResponse {
+            token_id,
+            token_uri,
+        })
+    }
+
+    pub fn get_provenance(deps: Deps, contract_address: String) -> StdResult<ProvenanceResponse> {
+        let launch = LAUNCHES.load(deps.storage, &deps.api.addr_validate(&contract_address)?)?;
+
+        Ok(ProvenanceResponse {
+            provenance_hash: launch.provenance_hash,
+            starting_index: launch.starting_index,
+        })
+    }
+
+    pub fn get_launch_status(
+        deps: Deps,
+        env: Env,
+        contract_address: String,
+    ) -> StdResult<LaunchStatus> {
+        let contract_address = deps.api.addr_validate(&contract_address)?;
+        let launch = LAUNCHES.load(deps.storage, &contract_address)?;
+
+        let status = match current_phase(&launch, env.block.time.seconds()) {
+            Some(phase) => phase.as_str().to_string(),
+            None => "closed".to_string(),
+        };
+
+        let tiers = launch
+            .tiers
+            .unwrap_or_default()
+            .into_iter()
+            .map(|tier| {
+                let minted = TIER_MINTED
+                    .may_load(deps.storage, (&contract_address, &tier.name))?
+                    .unwrap_or_default();
+                Ok(TierStatus {
+                    remaining: tier.supply.saturating_sub(minted),
+                    name: tier.name,
+                    supply: tier.supply,
+                })
+            })
+            .collect::<StdResult<_>>()?;
+
+        Ok(LaunchStatus { status, tiers })
+    }
+
+    pub fn get_whitelist_status(
+        deps: Deps,
+        contract_address: String,
+        account_address: String,
+        proof: Vec<String>,
+        allocation: Option<WhitelistAllocation>,
+    ) -> StdResult<WhitelistStatus> {
+        // verify merkle root
+        let contract_address = deps.api.addr_validate(&contract_address)?;
+        let launch = LAUNCHES.load(deps.storage, &contract_address)?;
+        let scheme = launch
+            .whitelist_merkle_scheme
+            .as_ref()
+            .unwrap_or(&MerkleScheme::V1);
+
+        let verified = match launch.whitelist_merkle_root {
+            Some(ref merkle_root) => whitelist_leaf(&launch, &account_address, allocation.as_ref())
+                .and_then(|leaf| {
+                    verify_merkle_proof(
+                        scheme,
+                        contract_address.as_str(),
+                        &leaf,
+                        merkle_root,
+                        &proof,
+                    )
+                }),
+            None => Err(ContractError::VerificationFailed {}),
+        };
+
+        match verified {
+            Ok(()) => Ok(WhitelistStatus {
+                is_whitelist: true,
+                allocation,
+            }),
+            Err(ContractError::VerificationFailed {}) => Ok(WhitelistStatus {
+                is_whitelist: false,
+                allocation: None,
+            }),
+            Err(err) => Err(StdError::generic_err(err.to_string())),
+        }
+    }
+
+    pub fn get_referrals(
+        deps: Deps,
+        contract_address: String,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    ) -> StdResult<ReferralsResponse> {
+        let contract_address = deps.api.addr_validate(&contract_address)?;
+        let start_after = start_after
+            .map(|address| deps.api.addr_validate(&address))
+            .transpose()?;
+        let start = start_after.as_ref().map(Bound::exclusive);
+        let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+
+        let referrals = REFERRAL_TOTALS
+            .prefix(&contract_address)
+            .range(deps.storage, start, None, Order::Ascending)
+            .take(limit)
+            .map(|item| {
+                let (referrer, total) = item?;
+                Ok(ReferralInfo {
+                    referrer: referrer.to_string(),
+                    minted: total.minted,
+                    earned: total.earned,
+                })
+            })
+            .collect::<StdResult<Vec<_>>>()?;
+
+        Ok(ReferralsResponse { referrals })
+    }
+
+    pub fn get_referral_rewards(deps: Deps, address: String) -> StdResult<ReferralRewardsResponse> {
+        let address = deps.api.addr_validate(&address)?;
+        let pending = REFERRAL_REWARDS
+            .may_load(deps.storage, &address)?
+            .unwrap_or_default();
+
+        Ok(ReferralRewardsResponse {
+            pending: Coin {
+                denom: NATIVE_DENOM.load(deps.storage)?,
+                amount: pending,
+            },
+        })
+    }
+
+    pub fn get_allowlist(
+        deps: Deps,
+        contract_address: String,
+        phase: Phase,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    ) -> StdResult<AllowlistResponse> {
+        let contract_address = deps.api.addr_validate(&contract_address)?;
+        let start_after = start_after
+            .map(|address| deps.api.addr_validate(&address))
+            .transpose()?;
+        let start = start_after.as_ref().map(Bound::exclusive);
+        let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+
+        let entries = ALLOWLIST
+            .prefix((&contract_address, phase.as_str()))
+            .range(deps.storage, start, None, Order::Ascending)
+            .take(limit)
+            .map(|item| {
+                let (address, entry) = item?;
+                Ok(AllowlistEntryInfo {
+                    address: address.to_string(),
+                    max_quantity: entry.max_quantity,
+                })
+            })
+            .collect::<StdResult<Vec<_>>>()?;
+
+        Ok(AllowlistResponse { entries })
+    }
+
+    pub fn get_archived_launch(
+        deps: Deps,
+        contract_address: String,
+    ) -> StdResult<ArchivedLaunchInfo> {
+        let contract_address = deps.api.addr_validate(&contract_address)?;
+        let archived = ARCHIVED_LAUNCHES.load(deps.storage, &contract_address)?;
+
+        Ok(archived_launch_info(contract_address, archived))
+    }
+
+    pub fn get_archived_launches(
+        deps: Deps,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    ) -> StdResult<ArchivedLaunchesResponse> {
+        let start_after = start_after
+            .map(|address| deps.api.addr_validate(&address))
+            .transpose()?;
+        let start = start_after.as_ref().map(Bound::exclusive);
+        let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+
+        let launches = ARCHIVED_LAUNCHES
+            .range(deps.storage, start, None, Order::Ascending)
+            .take(limit)
+            .map(|item| {
+                let (contract_address, archived) = item?;
+                Ok(archived_launch_info(contract_address, archived))
+            })
+            .collect::<StdResult<Vec<_>>>()?;
+
+        Ok(ArchivedLaunchesResponse { launches })
+    }
+
+    fn archived_launch_info(
+        contract_address: Addr,
+        archived: ArchivedLaunch,
+    ) -> ArchivedLaunchInfo {
+        ArchivedLaunchInfo {
+            contract_address,
+            launch: archived.launch,
+            reason: archived.reason,
+            archived_at: archived.archived_at,
+        }
+    }
+}
+
+fn assert_launch_owner_or_admin(
+    storage: &dyn Storage,
+    sender: &Addr,
+    launch: &Launch,
+) -> Result<(), ContractError> {
+    if cw_ownable::assert_owner(storage, sender).is_err() && *sender != launch.owner_address {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    Ok(())
+}
+
+fn current_phase(launch: &Launch, current_timestamp_in_seconds: u64) -> Option<Phase> {
+    if current_timestamp_in_seconds > launch.whitelist_started_at
+        && current_timestamp_in_seconds < launch.whitelist_ended_at
+    {
+        Some(Phase::Whitelist)
+    } else if current_timestamp_in_seconds > launch.public_started_at
+        && current_timestamp_in_seconds < launch.public_ended_at
+    {
+        Some(Phase::Public)
+    } else {
+        None
+    }
+}
+
+fn changed_fields(current: &Launch, launch: &Launch) -> Vec<&'static str> {
+    [
+        ("max_supply", current.max_supply != launch.max_supply),
+        ("base_uri", current.base_uri != launch.base_uri),
+        (
+            "is_base_uri_static",
+            current.is_base_uri_static != launch.is_base_uri_static,
+        ),
+        (
+            "media_extension",
+            current.media_extension != launch.media_extension,
+        ),
+        ("uri_template", current.uri_template != launch.uri_template),
+        (
+            "whitelist_price",
+            current.whitelist_price != launch.whitelist_price,
+        ),
+        (
+            "whitelist_max_buy",
+            current.whitelist_max_buy != launch.whitelist_max_buy,
+        ),
+        (
+            "whitelist_started_at",
+            current.whitelist_started_at != launch.whitelist_started_at,
+        ),
+        (
+            "whitelist_ended_at",
+            current.whitelist_ended_at != launch.whitelist_ended_at,
+        ),
+        ("public_price", current.public_price != launch.public_price),
+        (
+            "public_max_buy",
+            current.public_max_buy != launch.public_max_buy,
+        ),
+        (
+            "public_started_at",
+            current.public_started_at != launch.public_started_at,
+        ),
+        (
+            "public_ended_at",
+            current.public_ended_at != launch.public_ended_at,
+        ),
+        (
+            "royalty_percentage",
+            current.royalty_percentage != launch.royalty_percentage,
+        ),
+        (
+            "royalty_payment_address",
+            current.royalty_payment_address != launch.royalty_payment_address,
+        ),
+        (
+            "whitelist_merkle_root",
+            current.whitelist_merkle_root != launch.whitelist_merkle_root,
+        ),
+        ("referral", current.referral != launch.referral),
+        (
+            "whitelist_signer",
+            current.whitelist_signer != launch.whitelist_signer,
+        ),
+        (
+            "whitelist_leaf_format",
+            current.whitelist_leaf_format != launch.whitelist_leaf_format,
+        ),
+        (
+            "whitelist_merkle_scheme",
+            current.whitelist_merkle_scheme != launch.whitelist_merkle_scheme,
+        ),
+        ("lock_policy", current.lock_policy != launch.lock_policy),
+        (
+            "reserved_supply",
+            current.reserved_supply != launch.reserved_supply,
+        ),
+        (
+            "free_mint_fee",
+            current.free_mint_fee != launch.free_mint_fee,
+        ),
+        ("mint_limits", current.mint_limits != launch.mint_limits),
+        (
+            "whitelist_holder_gate",
+            current.whitelist_holder_gate != launch.whitelist_holder_gate,
+        ),
+        (
+            "public_holder_gate",
+            current.public_holder_gate != launch.public_holder_gate,
+        ),
+        ("trade_in", current.trade_in != launch.trade_in),
+        (
+            "whitelist_balance_gate",
+            current.whitelist_balance_gate != launch.whitelist_balance_gate,
+        ),
+        (
+            "public_balance_gate",
+            current.public_balance_gate != launch.public_balance_gate,
+        ),
+        ("raffle", current.raffle != launch.raffle),
+        ("tiers", current.tiers != launch.tiers),
+        (
+            "token_id_scheme",
+            current.token_id_scheme != launch.token_id_scheme,
+        ),
+        (
+            "provenance_hash",
+            current.provenance_hash != launch.provenance_hash,
+        ),
+    ]
+    .into_iter()
+    .filter(|(_, changed)| *changed)
+    .map(|(field, _)| field)
+    .collect()
+}
+
+// free phases take no funds, or only the anti-bot fee when one is set
+fn collect_payment(
+    info: &MessageInfo,
+    launch: &Launch,
+    denom: &str,
+    price: Uint128,
+    quantity: u64,
+) -> Result<Uint128, ContractError> {
+    let (denom, price) = match launch.free_mint_fee {
+        Some(ref free_mint_fee) if price.is_zero() => {
+            (free_mint_fee.denom.as_str(), free_mint_fee.amount)
+        }
+        None if price.is_zero() => {
+            cw_utils::nonpayable(info)?;
+            return Ok(Uint128::zero());
+        }
+        _ => (denom, price),
+    };
+
+    let fund_input = cw_utils::must_pay(info, denom)?;
+    if fund_input < price * Uint128::from(quantity) {
+        return Err(ContractError::InsufficientFunds {});
+    }
+
+    Ok(fund_input)
+}
+
+fn check_mint_limits(
+    storage: &mut dyn Storage,
+    querier: &QuerierWrapper,
+    env: &Env,
+    contract_address: &Addr,
+    account_address: &Addr,
+    mint_limits: &MintLimits,
+    quantity: u64,
+) -> Result<(), ContractError> {
+    if matches!(mint_limits.max_per_tx, Some(max_per_tx) if quantity > u64::from(max_per_tx)) {
+        return Err(ContractError::MintLimitExceeded {});
+    }
+
+    // only contracts have contract info
+    if mint_limits.reject_contract_callers
+        && querier
+            .query::<ContractInfoResponse>(
+                &WasmQuery::ContractInfo {
+                    contract_addr: account_address.to_string(),
+                }
+                .into(),
+            )
+            .is_ok()
+    {
+        return Err(ContractError::ContractCallerRejected {});
+    }
+
+    if let Some(max_per_block) = mint_limits.max_per_block {
+        let minted = match BLOCK_MINTS.may_load(storage, contract_address)? {
+            Some((height, minted)) if height == env.block.height => minted,
+            _ => 0,
+        };
+        if minted + quantity > u64::from(max_per_block) {
+            return Err(ContractError::MintLimitExceeded {});
+        }
+        BLOCK_MINTS.save(
+            storage,
+            contract_address,
+            &(env.block.height, minted + quantity),
+        )?;
+    }
+
+    Ok(())
+}
+
+fn validate_holder_gate(api: &dyn Api, holder_gate: &HolderGate) -> Result<(), ContractError> {
+    api.addr_validate(holder_gate.collection.as_str())?;
+    // the holdings are listed with a single page query
+    if holder_gate.min_count == 0 || holder_gate.min_count > 100 {
+        return Err(ContractError::InvalidHolderGate {});
+    }
+    if holder_gate.max_mints_per_token == Some(0) {
+        return Err(ContractError::InvalidHolderGate {});
+    }
+
+    Ok(())
+}
+
+fn check_holder_gate(
+    storage: &mut dyn Storage,
+    querier: &QuerierWrapper,
+    contract_address: &Addr,
+    phase: Phase,
+    account_address: &Addr,
+    holder_gate: &HolderGate,
+    gate_token: Option<&str>,
+    quantity: u64,
+) -> Result<(), ContractError> {
+    // counting mints per token needs to know which token is used
+    if gate_token.is_none()
+        && (holder_gate.require_token_proof || holder_gate.max_mints_per_token.is_some())
+    {
+        return Err(ContractError::MissingGateToken {});
+    }
+
+    if let Some(gate_token) = gate_token {
+        let owner_of: OwnerOfResponse = querier.query_wasm_smart(
+            holder_gate.collection.to_string(),
+            &Cw721QueryMsg::OwnerOf {
+                token_id: gate_token.to_string(),
+                include_expired: None,
+            },
+        )?;
+        if owner_of.owner != account_address.as_str() {
+            return Err(ContractError::NotHolder {});
+        }
+    }
+
+    if gate_token.is_none() || holder_gate.min_count > 1 {
+        let tokens: TokensResponse = querier.query_wasm_smart(
+            holder_gate.collection.to_string(),
+            &Cw721QueryMsg::Tokens {
+                owner: account_address.to_string(),
+                start_after: None,
+                limit: Some(holder_gate.min_count),
+            },
+        )?;
+        if tokens.tokens.len() < holder_gate.min_count as usize {
+            return Err(ContractError::NotHolder {});
+        }
+    }
+
+    if let (Some(max_mints_per_token), Some(gate_token)) =
+        (holder_gate.max_mints_per_token, gate_token)
+    {
+        let key = (contract_address, phase.as_str(), gate_token);
+        let minted = GATE_TOKEN_MINTS.may_load(storage, key)?.unwrap_or(0);
+        if u64::from(minted) + quantity > u64::from(max_mints_per_token) {
+            return Err(ContractError::GateTokenExhausted {});
+        }
+        GATE_TOKEN_MINTS.save(storage, key, &(minted + quantity as u32))?;
+    }
+
+    Ok(())
+}
+
+fn validate_balance_gate(
+    api: &dyn Api,
+    env: &Env,
+    balance_gate: &BalanceGate,
+) -> Result<(), ContractError> {
+    let (contract, snapshot) = match balance_gate.source {
+        BalanceSource::Cw20 { ref contract } => (contract, false),
+        BalanceSource::Staking { ref contract } => (contract, true),
+    };
+    api.addr_validate(contract.as_str())?;
+
+    // a snapshot must already be final, later heights still follow the live balance
+    let snapshot_height_valid = match balance_gate.snapshot_height {
+        Some(height) => snapshot && height <= env.block.height,
+        None => !snapshot,
+    };
+    if !snapshot_height_valid {
+        return Err(ContractError::InvalidBalanceGate {});
+    }
+
+    let ascending = balance_gate
+        .tiers
+        .windows(2)
+        .all(|tiers| tiers[0].min_balance < tiers[1].min_balance);
+    if balance_gate.tiers.is_empty()
+        || balance_gate.tiers.len() > 20
+        || !ascending
+        || balance_gate.tiers.iter().any(|tier| tier.max_buy == 0)
+    {
+        return Err(ContractError::InvalidBalanceGate {});
+    }
+
+    Ok(())
+}
+
+// the wallet quota of the highest tier the balance reaches
+fn balance_gate_max_buy(
+    querier: &QuerierWrapper,
+    account_address: &Addr,
+    balance_gate: &BalanceGate,
+) -> Result<u32, ContractError> {
+    let balance = match balance_gate.source {
+        BalanceSource::Cw20 { ref contract } => {
+            querier
+                .query_wasm_smart::<BalanceResponse>(
+                    contract.to_string(),
+                    &BalanceGateQueryMsg::Balance {
+                        address: account_address.to_string(),
+                    },
+                )?
+                .balance
+        }
+        BalanceSource::Staking { ref contract } => {
+            querier
+                .query_wasm_smart::<StakedBalanceAtHeightResponse>(
+                    contract.to_string(),
+                    &BalanceGateQueryMsg::StakedBalanceAtHeight {
+                        address: account_address.to_string(),
+                        height: balance_gate.snapshot_height,
+                    },
+                )?
+                .balance
+        }
+    };
+
+    balance_gate
+        .tiers
+        .iter()
+        .rev()
+        .find(|tier| balance >= tier.min_balance)
+        .map(|tier| tier.max_buy)
+        .ok_or(ContractError::InsufficientBalance {})
+}
+
+// deterministic draw randomness for the given shuffle position
+fn raffle_random(seed: &str, position: u32) -> u64 {
+    let hash = sha2::Sha256::digest(&[seed.as_bytes(), &position.to_be_bytes()].concat());
+    let mut bytes = [0; 8];
+    bytes.copy_from_slice(&hash[..8]);
+    u64::from_be_bytes(bytes)
+}
+
+// offset into the provenance order, seeded by the block that ends the sale
+fn starting_index(env: &Env, contract_address: &Addr, launch: &Launch) -> StdResult<u64> {
+    let supply = launch
+        .max_supply
+        .or(launch.edition_size)
+        .unwrap_or(launch.last_token_id)
+        .max(1);
+    let seed = format!(
+        "{}:{}:{}:{}:{}",
+        launch.provenance_hash.as_deref().unwrap_or_default(),
+        contract_address,
+        env.block.height,
+        env.block.time.nanos(),
+        launch.last_token_id
+    );
+    let hash = sha2::Sha256::digest(seed.as_bytes());
+    let mut bytes = [0; 8];
+    bytes.copy_from_slice(&hash[..8]);
+
+    Ok(u64::from_be_bytes(bytes) % supply)
+}
+
+// first id of the sequence the launch numbers its tokens in
+fn first_token_id(launch: &Launch) -> u64 {
+    match launch.token_id_scheme {
+        Some(TokenIdScheme::Sequential { start, .. }) => start,
+        _ => 1,
+    }
+}
+
+// id of the number-th token of the launch, or of the tier when given
+fn token_id(
+    storage: &dyn Storage,
+    launch: &Launch,
+    contract_address: &Addr,
+    tier: Option<&Tier>,
+    number: u64,
+) -> Result<String, ContractError> {
+    if let Some(tier) = tier {
+        return Ok(format!("{}{}", tier.token_id_prefix, number));
+    }
+
+    match launch.token_id_scheme {
+        None => Ok(number.to_string()),
+        Some(TokenIdScheme::Sequential {
+            start,
+            ref prefix,
+            padding,
+        }) => {
+            let id = number
+                .checked_sub(1)
+                .and_then(|offset| offset.checked_add(start))
+                .ok_or(ContractError::TokenIdsExhausted {})?;
+            Ok(format!(
+                "{}{:0>width$}",
+                prefix.as_deref().unwrap_or_default(),
+                id,
+                width = usize::from(padding.unwrap_or_default())
+            ))
+        }
+        Some(TokenIdScheme::List {}) => TOKEN_IDS
+            .may_load(storage, (contract_address, number))?
+            .ok_or(ContractError::TokenIdsExhausted {}),
+    }
+}
+
+// uri of the number-th token of the launch, or of the tier when given
+fn token_uri(
+    launch: &Launch,
+    contract_address: &Addr,
+    tier: Option<&Tier>,
+    number: u64,
+    token_id: &str,
+) -> Result<String, ContractError> {
+    if let Some(tier) = tier {
+        return render_token_uri(
+            &tier.uri_template,
+            &TokenUriVars {
+                id: number,
+                first_id: 1,
+                shuffle_key: &format!("{}:{}", contract_address, tier.name),
+                shuffle_range: Some(tier.supply),
+                tier: Some(&tier.name),
+            },
+        );
+    }
+
+    match launch.uri_template {
+        Some(ref uri_template) => render_token_uri(
+            uri_template,
+            &TokenUriVars {
+                id: number,
+                first_id: first_token_id(launch),
+                shuffle_key: contract_address.as_str(),
+                shuffle_range: launch.max_supply,
+                tier: None,
+            },
+        ),
+        None if launch.is_base_uri_static => Ok(launch.base_uri.clone()),
+        None => Ok(format!(
+            "{}/{}.{}",
+            launch.base_uri,
+            token_id,
+            launch.media_extension.as_deref().unwrap_or("png")
+        )),
+    }
+}
+
+// tiers are sold at their own price in every phase
+fn tier_prices(launch: &Launch) -> Option<Vec<(&str, &Coin)>> {
+    launch.tiers.as_ref().map(|tiers| {
+        tiers
+            .iter()
+            .map(|tier| (tier.name.as_str(), &tier.price))
+            .collect()
+    })
+}
+
+// what a locked launch still lets its operator change
+fn check_launch_lock(current: &Launch, launch: &Launch) -> Result<(), ContractError> {
+    if current.locked_at.is_none() {
+        return Ok(());
+    }
+
+    let policy = current.lock_policy.clone().unwrap_or_default();
+    let base_uri_changed = token_uris_changed(current, launch);
+    let price_changed = launch.whitelist_price != current.whitelist_price
+        || launch.public_price != current.public_price
+        || tier_prices(launch) != tier_prices(current);
+
+    // an open edition is the largest supply there is
+    let supply_raised = match (current.max_supply, launch.max_supply) {
+        (Some(current_max_supply), Some(max_supply)) => max_supply > current_max_supply,
+        (Some(_), None) => true,
+        (None, _) => false,
+    };
+
+    // a bigger reserve would hand unsold tokens to the owner for free
+    let reserve_raised =
+        launch.reserved_supply.unwrap_or_default() > current.reserved_supply.unwrap_or_default();
+
+    if (policy.supply_decrease_only && supply_raised)
+        || reserve_raised
+        || (policy.freeze_base_uri && base_uri_changed)
+        || (policy.freeze_prices && price_changed)
+        || launch.lock_policy != current.lock_policy
+        || launch.token_id_scheme != current.token_id_scheme
+        || launch.provenance_hash != current.provenance_hash
+    {
+        return Err(ContractError::LaunchLocked {});
+    }
+
+    Ok(())
+}
+
+// a shuffled template re-orders minted tokens when its range changes
+fn token_uris_changed(current: &Launch, launch: &Launch) -> bool {
+    let shuffle_range = |launch: &Launch| {
+        launch
+            .uri_template
+            .as_deref()
+            .filter(|uri_template| uses_shuffled_id(uri_template))
+            .and(launch.max_supply)
+    };
+    let tier_uris = |launch: &Launch| {
+        launch
+            .tiers
+            .iter()
+            .flatten()
+            .map(|tier| {
+                let shuffle_range = uses_shuffled_id(&tier.uri_template).then_some(tier.supply);
+                (tier.name.clone(), tier.uri_template.clone(), shuffle_range)
+            })
+            .collect::<Vec<_>>()
+    };
+
+    launch.base_uri != current.base_uri
+        || launch.is_base_uri_static != current.is_base_uri_static
+        || launch.media_extension != current.media_extension
+        || launch.uri_template != current.uri_template
+        || shuffle_range(launch) != shuffle_range(current)
+        || tier_uris(launch) != tier_uris(current)
+}
+
+fn validate_tiers(tiers: &[Tier], native_denom: &str) -> Result<(), ContractError> {
+    if tiers.is_empty() || tiers.len() > 10 {
+        return Err(ContractError::InvalidTiers {});
+    }
+    for (index, tier) in tiers.iter().enumerate() {
+        // a shared or missing prefix would mint colliding token ids
+        let duplicate = tiers[..index]
+            .iter()
+            .any(|other| other.name == tier.name || other.token_id_prefix == tier.token_id_prefix);
+        if duplicate || tier.name.is_empty() || tier.token_id_prefix.is_empty() || tier.supply == 0
+        {
+            return Err(ContractError::InvalidTiers {});
+        }
+        if tier.price.denom != native_denom {
+            return Err(ContractError::DenomNotSupported {});
+        }
+    }
+
+    Ok(())
+}
+
+fn validate_trade_in(api: &dyn Api, trade_in: &TradeIn) -> Result<(), ContractError> {
+    api.addr_validate(trade_in.collection.as_str())?;
+    if let Some(ref sink) = trade_in.sink {
+        api.addr_validate(sink.as_str())?;
+    }
+
+    Ok(())
+}
+
+// holder and balance gates of the phase, returns the quota a balance gate grants
+fn check_phase_gates(
+    deps: DepsMut,
+    contract_address: &Addr,
+    launch: &Launch,
+    phase: &Phase,
+    account_address: &Addr,
+    gate_token: Option<&str>,
+    quantity: u64,
+) -> Result<Option<u32>, ContractError> {
+    let (holder_gate, balance_gate) = match phase {
+        Phase::Whitelist => (
+            &launch.whitelist_holder_gate,
+            &launch.whitelist_balance_gate,
+        ),
+        Phase::Public => (&launch.public_holder_gate, &launch.public_balance_gate),
+    };
+    if let Some(holder_gate) = holder_gate {
+        check_holder_gate(
+            deps.storage,
+            &deps.querier,
+            contract_address,
+            phase.clone(),
+            account_address,
+            holder_gate,
+            gate_token,
+            quantity,
+        )?;
+    }
+
+    balance_gate
+        .as_ref()
+        .map(|balance_gate| balance_gate_max_buy(&deps.querier, account_address, balance_gate))
+        .transpose()
+}
+
+// counted against the receiver and the paying wallet alike
+fn consume_phase_quota(
+    storage: &mut dyn Storage,
+    contract_address: &Addr,
+    phase: &Phase,
+    receiver_address: &Addr,
+    payer_address: &Addr,
+    quantity: u64,
+    max_buy: u64,
+) -> Result<(), ContractError> {
+    let items_key = format!("{}-{}items", contract_address, phase.as_str());
+    consume_quota(storage, &items_key, receiver_address, quantity, max_buy)?;
+    let payer_items_key = format!("{}-{}payeritems", contract_address, phase.as_str());
+    consume_quota(storage, &payer_items_key, payer_address, quantity, max_buy)
+}
+
+fn consume_quota(
+    storage: &mut dyn Storage,
+    namespace: &str,
+    address: &Addr,
+    quantity: u64,
+    max_buy: u64,
+) -> Result<(), ContractError> {
+    let items: Map<&Addr, u64> = Map::new(namespace);
+
+    let previous_items = items.load(storage, address).unwrap_or(0);
+    if previous_items + quantity > max_buy {
+        return Err(ContractError::MintQuotaExhausted {});
+    }
+    items.save(storage, address, &(previous_items + quantity))?;
+
+    Ok(())
+}
+
+// tokens left for sale, unbounded for open editions
+fn available_supply(launch: &Launch) -> Option<u64> {
+    launch.max_supply.map(|max_supply| {
+        max_supply
+            .saturating_sub(launch.last_token_id)
+            .saturating_sub(reserved_remaining(launch))
+    })
+}
+
+fn reserved_remaining(launch: &Launch) -> u64 {
+    launch
+        .reserved_supply
+        .unwrap_or(0)
+        .saturating_sub(launch.reserved_minted)
+}
+
+fn validate_launch(
+    storage: &dyn Storage,
+    contract_address: &Addr,
+    launch: &Launch,
+) -> Result<(), ContractError> {
+    match launch.max_supply {
+        Some(0) => return Err(ContractError::InvalidMaxSupply {}),
+        Some(max_supply) if max_supply < launch.last_token_id => {
+            return Err(ContractError::MaxSupplyBelowMinted {})
+        }
+        // every token of an open edition shares the artwork, unless a template names them
+        None if !launch.is_base_uri_static && launch.uri_template.is_none() => {
+            return Err(ContractError::InvalidOpenEdition {})
+        }
+        _ => {}
+    }
+    if let Some(ref provenance_hash) = launch.provenance_hash {
+        parse_hash(provenance_hash).map_err(|_| ContractError::InvalidProvenanceHash {})?;
+    }
+    if let Some(TokenIdScheme::Sequential {
+        ref prefix,
+        padding,
+        ..
+    }) = launch.token_id_scheme
+    {
+        if prefix.as_ref().is_some_and(|prefix| prefix.len() > 64)
+            || padding.is_some_and(|padding| padding > 20)
+        {
+            return Err(ContractError::InvalidTokenIdScheme {});
+        }
+    }
+    if let Some(ref uri_template) = launch.uri_template {
+        validate_uri_template(uri_template, launch.max_supply, false)?;
+    }
+    if let Some(ref tiers) = launch.tiers {
+        let tier_supply: u64 = tiers.iter().map(|tier| tier.supply).sum();
+        if matches!(launch.max_supply, Some(max_supply) if tier_supply > max_supply) {
+            return Err(ContractError::InvalidTiers {});
+        }
+        // raffle claims, trade-ins and airdrops mint without a tier
+        if launch.raffle.is_some() || launch.trade_in.is_some() || launch.reserved_supply.is_some()
+        {
+            return Err(ContractError::InvalidTiers {});
+        }
+        for tier in tiers {
+            let minted = TIER_MINTED
+                .may_load(storage, (contract_address, &tier.name))?
+                .unwrap_or_default();
+            if tier.supply < minted {
+                return Err(ContractError::InvalidTiers {});
+            }
+            validate_uri_template(&tier.uri_template, Some(tier.supply), true)?;
+        }
+    }
+    if let Some(ref raffle) = launch.raffle {
+        if raffle.winners == 0
+            || raffle.registration_ended_at <= raffle.registration_started_at
+            || raffle.draw_deadline <= raffle.registration_ended_at
+            || raffle
+                .seed_commitment
+                .as_deref()
+                .is_some_and(|seed_commitment| parse_hash(seed_commitment).is_err())
+        {
+            return Err(ContractError::InvalidRaffle {});
+        }
+    }
+    if let Some(reserved_supply) = launch.reserved_supply {
+        let unsold = launch
+            .max_supply
+            .map(|max_supply| max_supply - launch.last_token_id);
+        if reserved_supply < launch.reserved_minted
+            || matches!(unsold, Some(unsold) if reserved_supply - launch.reserved_minted > unsold)
+        {
+            return Err(ContractError::InvalidReservedSupply {});
+        }
+    }
+    if launch.whitelist_ended_at < launch.whitelist_started_at
+        || launch.public_ended_at < launch.public_started_at
+    {
+        return Err(ContractError::InvalidSchedule {});
+    }
+
+    // a zero length window disables the phase
+    let has_whitelist = launch.whitelist_ended_at > launch.whitelist_started_at;
+    let has_public = launch.public_ended_at > launch.public_started_at;
+    if has_whitelist
+        && has_public
+        && launch.whitelist_started_at < launch.public_ended_at
+        && launch.public_started_at < launch.whitelist_ended_at
+    {
+        return Err(ContractError::OverlappingPhases {});
+    }
+
+    Ok(())
+}
+
+// a whitelist phase that is still to come needs something to admit its buyers with
+fn validate_whitelist_source(
+    storage: &dyn Storage,
+    contract_address: &Addr,
+    launch: &Launch,
+    now: u64,
+) -> Result<(), ContractError> {
+    let has_whitelist = launch.whitelist_ended_at > launch.whitelist_started_at;
+    if has_whitelist
+        && now < launch.whitelist_ended_at
+        && launch.whitelist_merkle_root.is_none()
+        && launch.whitelist_signer.is_none()
+        && launch.whitelist_holder_gate.is_none()
+        && launch.whitelist_balance_gate.is_none()
+    {
+        let has_allowlist = ALLOWLIST
+            .prefix((contract_address, Phase::Whitelist.as_str()))
+            .range(storage, None, None, Order::Ascending)
+            .next()
+            .is_some();
+        if !has_allowlist {
+            return Err(ContractError::MissingWhitelistSource {});
+        }
+    }
+
+    Ok(())
+}
+
+fn validate_voucher_signer(signer: &VoucherSigner) -> Result<(), ContractError> {
+    let valid_length = match signer.algorithm {
+        // compressed or uncompressed SEC1 key
+        SignatureAlgorithm::Secp256k1 => matches!(signer.public_key.len(), 33 | 65),
+        SignatureAlgorithm::Ed25519 => signer.public_key.len() == 32,
+    };
+    if !valid_length {
+        return Err(ContractError::InvalidPublicKey {});
+    }
+
+    Ok(())
+}
+
+// checks a whitelist voucher against the launch signer and records its use
+fn use_voucher(
+    storage: &mut dyn Storage,
+    api: &dyn Api,
+    current_timestamp_in_seconds: u64,
+    launch: &Launch,
+    contract_address: &Addr,
+    account_address: &Addr,
+    voucher: Voucher,
+    quantity: u64,
+) -> Result<(), ContractError> {
+    let signer = launch
+        .whitelist_signer
+        .as_ref()
+        .ok_or(ContractError::InvalidVoucher {})?;
+
+    if voucher.wallet != account_address.as_str()
+        || voucher.launch != contract_address.as_str()
+        || voucher.phase != Phase::Whitelist
+    {
+        return Err(ContractError::InvalidVoucher {});
+    }
+    if voucher.expires_at.u64() <= current_timestamp_in_seconds {
+        return Err(ContractError::VoucherExpired {});
+    }
+
+    let message_hash = sha2::Sha256::digest(to_binary(&voucher.payload())?.as_slice());
+    let verified = match signer.algorithm {
+        SignatureAlgorithm::Secp256k1 => {
+            api.secp256k1_verify(&message_hash, &voucher.signature, &signer.public_key)?
+        }
+        SignatureAlgorithm::Ed25519 => {
+            api.ed25519_verify(&message_hash, &voucher.signature, &signer.public_key)?
+        }
+    };
+    if !verified {
+        return Err(ContractError::VerificationFailed {});
+    }
+
+    let key = (contract_address, voucher.nonce.as_str());
+    let uses = u64::from(VOUCHER_USES.may_load(storage, key)?.unwrap_or(0));
+    if uses + quantity > u64::from(voucher.max_quantity) {
+        return Err(ContractError::VoucherExhausted {});
+    }
+    VOUCHER_USES.save(storage, key, &((uses + quantity) as u32))?;
+
+    Ok(())
+}
+
+// preimage of the merkle leaf for an account, depending on the launch leaf format
+fn whitelist_leaf(
+    launch: &Launch,
+    account_address: &str,
+    allocation: Option<&WhitelistAllocation>,
+) -> Result<String, ContractError> {
+    match (&launch.whitelist_leaf_format, allocation) {
+        (None | Some(LeafFormat::Address), None) => Ok(account_address.to_string()),
+        (None | Some(LeafFormat::Address), Some(_)) => Err(ContractError::InvalidAllocation {}),
+        (Some(LeafFormat::AddressAllocation), Some(allocation)) => Ok(format!(
+            "{}|{}|{}",
+            account_address, allocation.max_quantity, allocation.price
+        )),
+        (Some(LeafFormat::AddressAllocation), None) => Err(ContractError::MissingAllocation {}),
+    }
+}
+
+// the tier with the highest threshold already passed wins, then the per-launch
+// override, then the global taker fee
+fn effective_taker_fee(
+    storage: &dyn Storage,
+    launch: &Launch,
+) -> StdResult<(u64, Option<FeeTier>)> {
+    let tier = launch.taker_fee_tiers.as_ref().and_then(|tiers| {
+        tiers
+            .iter()
+            .rev()
+            .find(|tier| launch.sold >= tier.min_sold)
+            .cloned()
+    });
+
+    let taker_fee = match (&tier, launch.taker_fee) {
+        (Some(tier), _) => tier.taker_fee,
+        (None, Some(taker_fee)) => taker_fee,
+        (None, None) => TAKERFEE.load(storage)?,
+    };
+
+    Ok((taker_fee, tier))
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info};
+    use cosmwasm_std::{
+        coin, coins, BankMsg, Coin, ContractResult, CosmosMsg, SystemError, SystemResult, Uint128,
+        Uint64, WasmMsg,
+    };
+    use cw721::Cw721ReceiveMsg;
+    use cw_utils::Expiration;
+
+    use crate::msg::{
+        LaunchResponse, LaunchStatus, ProvenanceResponse, RaffleResponse, ReferralInfo,
+        TokenUriResponse,
+    };
+    use crate::state::{BalanceTier, ReferralConfig};
+
+    const DENOM: &str = "aconst";
+    const COLLECTION: &str = "collection";
+
+    fn setup_launch(mut deps: DepsMut) {
+        let msg = InstantiateMsg {
+            taker_fee: Uint64::new(10),
+            native_denom: DENOM.to_string(),
+            taker_address: "taker".to_string(),
+        };
+        instantiate(deps.branch(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+
+        execute(
+            deps,
+            mock_env(),
+            mock_info("creator", &[]),
+            add_launch_msg(),
+        )
+        .unwrap();
+    }
+
+    pub(crate) fn add_launch_msg() -> ExecuteMsg {
+        let now = mock_env().block.time.seconds();
+        ExecuteMsg::AddLaunch {
+            owner_address: "owner".to_string(),
+            contract_address: COLLECTION.to_string(),
+            max_supply: Some(10),
+            base_uri: "ipfs://base".to_string(),
+            is_base_uri_static: false,
+            media_extension: None,
+            uri_template: None,
+            whitelist_price: coin(50, DENOM),
+            whitelist_max_buy: None,
+            whitelist_started_at: Uint64::zero(),
+            whitelist_ended_at: Uint64::zero(),
+            public_price: coin(100, DENOM),
+            public_max_buy: None,
+            public_started_at: Uint64::new(now - 100),
+            public_ended_at: Uint64::new(now + 100),
+            royalty_percentage: None,
+            royalty_payment_address: None,
+            whitelist_merkle_root: None,
+            referral: None,
+            whitelist_signer: None,
+            whitelist_leaf_format: None,
+            whitelist_merkle_scheme: None,
+            lock_policy: None,
+            reserved_supply: None,
+            free_mint_fee: None,
+            mint_limits: None,
+            whitelist_holder_gate: None,
+            public_holder_gate: None,
+            trade_in: None,
+            whitelist_balance_gate: None,
+            public_balance_gate: None,
+            raffle: None,
+            tiers: None,
+            token_id_scheme: None,
+            provenance_hash: None,
+        }
+    }
+
+    // keeps every field of the launch
+    fn modify_launch_msg(contract_address: &str) -> ExecuteMsg {
+        ExecuteMsg::ModifyLaunch {
+            contract_address: contract_address.to_string(),
+            max_supply: Update::Keep,
+            base_uri: None,
+            is_base_uri_static: None,
+            media_extension: Update::Keep,
+            uri_template: Update::Keep,
+            whitelist_price: None,
+            whitelist_max_buy: Update::Keep,
+            whitelist_started_at: None,
+            whitelist_ended_at: None,
+            public_price: None,
+            public_max_buy: Update::Keep,
+            public_started_at: None,
+            public_ended_at: None,
+            royalty_percentage: Update::Keep,
+            royalty_payment_address: Update::Keep,
+            whitelist_merkle_root: Update::Keep,
+            referral: Update::Keep,
+            whitelist_signer: Update::Keep,
+            whitelist_leaf_format: Update::Keep,
+            whitelist_merkle_scheme: Update::Keep,
+            lock_policy: Update::Keep,
+            reserved_supply: Update::Keep,
+            free_mint_fee: Update::Keep,
+            mint_limits: Update::Keep,
+            whitelist_holder_gate: Update::Keep,
+            public_holder_gate: Update::Keep,
+            trade_in: Update::Keep,
+            whitelist_balance_gate: Update::Keep,
+            public_balance_gate: Update::Keep,
+            raffle: Update::Keep,
+            tiers: Update::Keep,
+            token_id_scheme: Update::Keep,
+            provenance_hash: Update::Keep,
+            force: None,
+        }
+    }
+
+    fn mint_msg() -> ExecuteMsg {
+        ExecuteMsg::Mint {
+            contract_address: COLLECTION.to_string(),
+            receiver_address: None,
+            proof: None,
+            referrer: None,
+            voucher: None,
+            allocation: None,
+            quantity: None,
+            gate_token: None,
+            tier: None,
+        }
+    }
+
+    #[test]
+    fn proper_initialization() {
+        let mut deps = mock_dependencies();
+
+        let msg = InstantiateMsg {
+            taker_fee: Uint64::new(10),
+            native_denom: "aconst".to_string(),
+            taker_address: "admin".to_string(),
+        };
+        let info = mock_info("creator", &coins(1000, "earth"));
+
+        // we can just call .unwrap() to assert this was a success
+        let res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+        assert_eq!(0, res.messages.len());
+    }
+
+    #[test]
+    fn launch_taker_fee_tiers() {
+        let mut deps = mock_dependencies();
+        setup_launch(deps.as_mut());
+
+        let msg = ExecuteMsg::ChangeLaunchTakerFee {
+            contract_address: COLLECTION.to_string(),
+            taker_fee: Update::Set(5),
+            taker_fee_tiers: Update::Set(vec![FeeTier {
+                min_sold: 1,
+                taker_fee: 2,
+            }]),
+        };
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("owner", &[]),
+            msg.clone(),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::Ownership(_)));
+        execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+
+        let launch = query::get_launch(deps.as_ref(), COLLECTION.to_string()).unwrap();
+        assert_eq!(launch.effective_taker_fee, Uint64::new(5));
+        assert_eq!(launch.taker_fee_tier, None);
+
+        // airdropped tokens were not sold
+        let mut msg = modify_launch_msg(COLLECTION);
+        if let ExecuteMsg::ModifyLaunch {
+            reserved_supply, ..
+        } = &mut msg
+        {
+            *reserved_supply = Update::Set(2);
+        }
+        execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), msg).unwrap();
+        let msg = ExecuteMsg::AirdropMint {
+            contract_address: COLLECTION.to_string(),
+            recipients: vec![("alice".to_string(), 2)],
+        };
+        execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), msg).unwrap();
+        let launch = query::get_launch(deps.as_ref(), COLLECTION.to_string()).unwrap();
+        assert_eq!(launch.effective_taker_fee, Uint64::new(5));
+
+        // the first sale pays the override, later sales fall into the tier
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("buyer", &coins(100, DENOM)),
+            mint_msg(),
+        )
+        .unwrap();
+        assert_eq!(
+            res.messages[1].msg,
+            BankMsg::Send {
+                to_address: "taker".to_string(),
+                amount: coins(5, DENOM),
+            }
+            .into()
+        );
+
+        let launch = query::get_launch(deps.as_ref(), COLLECTION.to_string()).unwrap();
+        assert_eq!(launch.effective_taker_fee, Uint64::new(2));
+        assert_eq!(
+            launch.taker_fee_tier,
+            Some(FeeTier {
+                min_sold: 1,
+                taker_fee: 2,
+            })
+        );
+
+        // clearing the tiers keeps the override
+        let msg = ExecuteMsg::ChangeLaunchTakerFee {
+            contract_address: COLLECTION.to_string(),
+            taker_fee: Update::Keep,
+            taker_fee_tiers: Update::Clear,
+        };
+        execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+        let launch = query::get_launch(deps.as_ref(), COLLECTION.to_string()).unwrap();
+        assert_eq!(launch.effective_taker_fee, Uint64::new(5));
+        assert_eq!(launch.launch.taker_fee_tiers, None);
+    }
+
+    #[test]
+    fn referral_rewards_accrue() {
+        let mut deps = mock_dependencies();
+        setup_launch(deps.as_mut());
+
+        let mut msg = modify_launch_msg(COLLECTION);
+        if let ExecuteMsg::ModifyLaunch { referral, .. } = &mut msg {
+            *referral = Update::Set(ReferralConfig {
+                share_percentage: 50,
+                accrue: true,
+            });
+        }
+        execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), msg).unwrap();
+
+        let mint_with_referrer = |address: &str| {
+            let mut msg = mint_msg();
+            if let ExecuteMsg::Mint { referrer, .. } = &mut msg {
+                *referrer = Some(address.to_string());
+            }
+            msg
+        };
+
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("buyer", &coins(100, DENOM)),
+            mint_with_referrer("buyer"),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::SelfReferral {}));
+
+        // 10 goes to the taker, half of the remaining 90 accrues to the referrer
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("buyer", &coins(100, DENOM)),
+            mint_with_referrer("affiliate"),
+        )
+        .unwrap();
+        assert_eq!(
+            res.messages[2].msg,
+            BankMsg::Send {
+                to_address: "owner".to_string(),
+                amount: coins(45, DENOM),
+            }
+            .into()
+        );
+
+        let referrals =
+            query::get_referrals(deps.as_ref(), COLLECTION.to_string(), None, None).unwrap();
+        assert_eq!(
+            referrals.referrals,
+            vec![ReferralInfo {
+                referrer: "affiliate".to_string(),
+                minted: 1,
+                earned: Uint128::new(45),
+            }]
+        );
+
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("affiliate", &[]),
+            ExecuteMsg::WithdrawReferralRewards {},
+        )
+        .unwrap();
+        assert_eq!(
+            res.messages[0].msg,
+            BankMsg::Send {
+                to_address: "affiliate".to_string(),
+                amount: coins(45, DENOM),
+            }
+            .into()
+        );
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("affiliate", &[]),
+            ExecuteMsg::WithdrawReferralRewards {},
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::NothingToWithdraw {}));
+    }
+
+    #[test]
+    fn whitelist_mint_with_signed_voucher() {
+        let mut deps = mock_dependencies();
+        setup_launch(deps.as_mut());
+
+        let now = mock_env().block.time.seconds();
+        let signer = VoucherSigner {
+            algorithm: SignatureAlgorithm::Ed25519,
+            public_key: Binary::from_base64("A6EHv/POEL4dcN0Y50vAmWfk1jCbpQ1fHdyGZBJVMbg=")
+                .unwrap(),
+        };
+        let mut msg = modify_launch_msg(COLLECTION);
+        if let ExecuteMsg::ModifyLaunch {
+            whitelist_started_at,
+            whitelist_ended_at,
+            public_started_at,
+            public_ended_at,
+            whitelist_signer,
+            ..
+        } = &mut msg
+        {
+            *whitelist_started_at = Some(Uint64::new(now - 100));
+            *whitelist_ended_at = Some(Uint64::new(now + 100));
+            *public_started_at = Some(Uint64::new(now + 100));
+            *public_ended_at = Some(Uint64::new(now + 200));
+            *whitelist_signer = Update::Set(signer);
+        }
+        execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), msg).unwrap();
+
+        // signed off-chain over sha256 of the JSON encoded payload
+        let voucher = Voucher {
+            wallet: "buyer".to_string(),
+            launch: COLLECTION.to_string(),
+            phase: Phase::Whitelist,
+            max_quantity: 1,
+            expires_at: Uint64::new(now + 100),
+            nonce: "n-1".to_string(),
+            signature: Binary::from_base64(
+                "57F3qmZjKzScb6p9ABxq70XtTZ26S2roELbge13U18VkZYoJiU/l5P5RWHDkqUACetX1gas7rQKt3eysB0E5Cg==",
+            )
+            .unwrap(),
+        };
+        let mint_with_voucher = |signed: Voucher| {
+            let mut msg = mint_msg();
+            if let ExecuteMsg::Mint { voucher, .. } = &mut msg {
+                *voucher = Some(signed);
+            }
+            msg
+        };
+
+        let mut forged = voucher.clone();
+        forged.max_quantity = 5;
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("buyer", &coins(50, DENOM)),
+            mint_with_voucher(forged),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::VerificationFailed {}));
+
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("buyer", &coins(50, DENOM)),
+            mint_with_voucher(voucher.clone()),
+        )
+        .unwrap();
+
+        // the nonce is spent once max_quantity mints have used it
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("buyer", &coins(50, DENOM)),
+            mint_with_voucher(voucher),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::VoucherExhausted {}));
+    }
+
+    #[test]
+    fn whitelist_mint_with_allocation_leaf() {
+        let mut deps = mock_dependencies();
+        setup_launch(deps.as_mut());
+
+        let leaf = |preimage: &str| -> [u8; 32] {
+            sha2::Sha256::digest(preimage.as_bytes())
+                .as_slice()
+                .try_into()
+                .unwrap()
+        };
+        let buyer_leaf = leaf("buyer|2|30");
+        let other_leaf = leaf("other|1|50");
+        let mut hashes = [buyer_leaf, other_leaf];
+        hashes.sort_unstable();
+        let root = hex::encode(sha2::Sha256::digest(&hashes.concat()));
+        let proof = vec![hex::encode(other_leaf)];
+
+        let now = mock_env().block.time.seconds();
+        let mut msg = modify_launch_msg(COLLECTION);
+        if let ExecuteMsg::ModifyLaunch {
+            whitelist_started_at,
+            whitelist_ended_at,
+            public_started_at,
+            public_ended_at,
+            whitelist_merkle_root,
+            whitelist_leaf_format,
+            ..
+        } = &mut msg
+        {
+            *whitelist_started_at = Some(Uint64::new(now - 100));
+            *whitelist_ended_at = Some(Uint64::new(now + 100));
+            *public_started_at = Some(Uint64::new(now + 100));
+            *public_ended_at = Some(Uint64::new(now + 200));
+            *whitelist_merkle_root = Update::Set(root);
+            *whitelist_leaf_format = Update::Set(LeafFormat::AddressAllocation);
+        }
+        execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), msg).unwrap();
+
+        let allocation = WhitelistAllocation {
+            max_quantity: 2,
+            price: Uint128::new(30),
+        };
+        let status = query::get_whitelist_status(
+            deps.as_ref(),
+            COLLECTION.to_string(),
+            "buyer".to_string(),
+            proof.clone(),
+            Some(allocation.clone()),
+        )
+        .unwrap();
+        assert_eq!(status.allocation, Some(allocation.clone()));
+
+        let mint_with_allocation = |claimed: Option<WhitelistAllocation>| {
+            let mut msg = mint_msg();
+            if let ExecuteMsg::Mint {
+                proof: msg_proof,
+                allocation,
+                ..
+            } = &mut msg
+            {
+                *msg_proof = Some(proof.clone());
+                *allocation = claimed;
+            }
+            msg
+        };
+
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("buyer", &coins(30, DENOM)),
+            mint_with_allocation(None),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::MissingAllocation {}));
+
+        // claiming a bigger allocation than the one in the tree fails the proof
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("buyer", &coins(30, DENOM)),
+            mint_with_allocation(Some(WhitelistAllocation {
+                max_quantity: 5,
+                price: Uint128::new(30),
+            })),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::VerificationFailed {}));
+
+        // the wallet price applies instead of the launch whitelist price
+        for _ in 0..2 {
+            execute(
+                deps.as_mut(),
+                mock_env(),
+                mock_info("buyer", &coins(30, DENOM)),
+                mint_with_allocation(Some(allocation.clone())),
+            )
+            .unwrap();
+        }
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("buyer", &coins(30, DENOM)),
+            mint_with_allocation(Some(allocation)),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::MintQuotaExhausted {}));
+
+        // a tier is sold at the tier price whatever the allocation says
+        let mut deps = mock_dependencies();
+        setup_launch(deps.as_mut());
+        let mut msg = modify_launch_msg(COLLECTION);
+        if let ExecuteMsg::ModifyLaunch {
+            whitelist_started_at,
+            whitelist_ended_at,
+            public_started_at,
+            public_ended_at,
+            whitelist_merkle_root,
+            whitelist_leaf_format,
+            tiers,
+            force,
+            ..
+        } = &mut msg
+        {
+            *force = Some(true);
+            *whitelist_started_at = Some(Uint64::new(now - 100));
+            *whitelist_ended_at = Some(Uint64::new(now + 100));
+            *public_started_at = Some(Uint64::new(now + 100));
+            *public_ended_at = Some(Uint64::new(now + 200));
+            *whitelist_merkle_root =
+                Update::Set(hex::encode(sha2::Sha256::digest(&hashes.concat())));
+            *whitelist_leaf_format = Update::Set(LeafFormat::AddressAllocation);
+            *tiers = Update::Set(vec![Tier {
+                name: "gold".to_string(),
+                supply: 2,
+                price: coin(300, DENOM),
+                token_id_prefix: "gold-".to_string(),
+                uri_template: "ipfs://gold/{id}.json".to_string(),
+            }]);
+        }
+        execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), msg).unwrap();
+
+        let mut msg = mint_msg();
+        if let ExecuteMsg::Mint {
+            proof: msg_proof,
+            allocation,
+            tier,
+            ..
+        } = &mut msg
+        {
+            *msg_proof = Some(vec![hex::encode(buyer_leaf)]);
+            *allocation = Some(WhitelistAllocation {
+                max_quantity: 1,
+                price: Uint128::new(50),
+            });
+            *tier = Some("gold".to_string());
+        }
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("other", &coins(50, DENOM)),
+            msg.clone(),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::InsufficientFunds {}));
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("other", &coins(300, DENOM)),
+            msg,
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn whitelist_mint_with_stored_allowlist() {
+        let mut deps = mock_dependencies();
+        let msg = InstantiateMsg {
+            taker_fee: Uint64::new(10),
+            native_denom: DENOM.to_string(),
+            taker_address: "taker".to_string(),
+        };
+        instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+
+        let now = mock_env().block.time.seconds();
+        let add_launch = |whitelist_started_at: u64| {
+            let mut msg = add_launch_msg();
+            if let ExecuteMsg::AddLaunch {
+                whitelist_started_at: msg_whitelist_started_at,
+                whitelist_ended_at,
+                public_started_at,
+                public_ended_at,
+                ..
+            } = &mut msg
+            {
+                *msg_whitelist_started_at = Uint64::new(whitelist_started_at);
+                *whitelist_ended_at = Uint64::new(now + 200);
+                *public_started_at = Uint64::new(now + 200);
+                *public_ended_at = Uint64::new(now + 300);
+            }
+            msg
+        };
+
+        // a live whitelist needs something to admit its buyers with right away
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("creator", &[]),
+            add_launch(now - 100),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::MissingWhitelistSource {}));
+        // an upcoming one gets its entries after the launch is added
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("creator", &[]),
+            add_launch(now + 100),
+        )
+        .unwrap();
+
+        let msg = ExecuteMsg::AddToAllowlist {
+            contract_address: COLLECTION.to_string(),
+            phase: Phase::Whitelist,
+            entries: vec![
+                AllowlistEntryInfo {
+                    address: "buyer".to_string(),
+                    max_quantity: Some(1),
+                },
+                AllowlistEntryInfo {
+                    address: "partner".to_string(),
+                    max_quantity: None,
+                },
+            ],
+        };
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("stranger", &[]),
+            msg.clone(),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized {}));
+        execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), msg).unwrap();
+
+        let page = query::get_allowlist(
+            deps.as_ref(),
+            COLLECTION.to_string(),
+            Phase::Whitelist,
+            Some("buyer".to_string()),
+            None,
+        )
+        .unwrap();
+        assert_eq!(
+            page.entries,
+            vec![AllowlistEntryInfo {
+                address: "partner".to_string(),
+                max_quantity: None,
+            }]
+        );
+
+        // no proof needed for stored entries, and the per-address quota applies
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(150);
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("buyer", &coins(50, DENOM)),
+            mint_msg(),
+        )
+        .unwrap();
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("buyer", &coins(50, DENOM)),
+            mint_msg(),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::MintQuotaExhausted {}));
+
+        let remove = |address: &str| ExecuteMsg::RemoveFromAllowlist {
+            contract_address: COLLECTION.to_string(),
+            phase: Phase::Whitelist,
+            addresses: vec![address.to_string()],
+        };
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("owner", &[]),
+            remove("partner"),
+        )
+        .unwrap();
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("partner", &coins(50, DENOM)),
+            mint_msg(),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::NotWhitelisted {}));
+
+        // the last stored entry is all that admits anyone to the live phase
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("owner", &[]),
+            remove("buyer"),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::MissingWhitelistSource {}));
+
+        // and can go once the phase is over
+        env.block.time = env.block.time.plus_seconds(100);
+        execute(deps.as_mut(), env, mock_info("owner", &[]), remove("buyer")).unwrap();
+    }
+
+    #[test]
+    fn launch_config_validation() {
+        let mut deps = mock_dependencies();
+        setup_launch(deps.as_mut());
+
+        let now = mock_env().block.time.seconds();
+        let modify = |max_supply: Option<u64>,
+                      whitelist_window: Option<(u64, u64)>,
+                      public_price: Option<Coin>,
+                      force: Option<bool>| {
+            let mut msg = modify_launch_msg(COLLECTION);
+            if let ExecuteMsg::ModifyLaunch {
+                max_supply: msg_max_supply,
+                whitelist_started_at,
+                whitelist_ended_at,
+                public_price: msg_public_price,
+                force: msg_force,
+                ..
+            } = &mut msg
+            {
+                *msg_max_supply = max_supply.map_or(Update::Keep, Update::Set);
+                *whitelist_started_at = whitelist_window.map(|(start, _)| Uint64::new(start));
+                *whitelist_ended_at = whitelist_window.map(|(_, end)| Uint64::new(end));
+                *msg_public_price = public_price;
+                *msg_force = force;
+            }
+            msg
+        };
+        let owner = mock_info("owner", &[]);
+
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            owner.clone(),
+            modify(Some(0), None, None, None),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::InvalidMaxSupply {}));
+
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            owner.clone(),
+            modify(None, Some((now + 300, now + 200)), None, None),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::InvalidSchedule {}));
+
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            owner.clone(),
+            modify(None, Some((now + 50, now + 200)), None, None),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::OverlappingPhases {}));
+
+        // no merkle root, signer or stored entries to admit anyone
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            owner.clone(),
+            modify(None, Some((now + 100, now + 200)), None, None),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::MissingWhitelistSource {}));
+
+        for _ in 0..2 {
+            execute(
+                deps.as_mut(),
+                mock_env(),
+                mock_info("buyer", &coins(100, DENOM)),
+                mint_msg(),
+            )
+            .unwrap();
+        }
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            owner.clone(),
+            modify(Some(1), None, None, Some(true)),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::MaxSupplyBelowMinted {}));
+
+        // the public phase is live, so repricing it needs to be forced
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            owner.clone(),
+            modify(None, None, Some(coin(200, DENOM)), None),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::PhaseActive {}));
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            owner.clone(),
+            modify(None, None, Some(coin(100, DENOM)), None),
+        )
+        .unwrap();
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            owner,
+            modify(None, None, Some(coin(200, DENOM)), Some(true)),
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn launch_locks_after_first_mint() {
+        let mut deps = mock_dependencies();
+        setup_launch(deps.as_mut());
+
+        let modify =
+            |max_supply: Option<u64>, base_uri: Option<&str>, lock_policy: Update<LockPolicy>| {
+                let mut msg = modify_launch_msg(COLLECTION);
+                if let ExecuteMsg::ModifyLaunch {
+                    max_supply: msg_max_supply,
+                    base_uri: msg_base_uri,
+                    lock_policy: msg_lock_policy,
+                    ..
+                } = &mut msg
+                {
+                    *msg_max_supply = max_supply.map_or(Update::Keep, Update::Set);
+                    *msg_base_uri = base_uri.map(|base_uri| base_uri.to_string());
+                    *msg_lock_policy = lock_policy;
+                }
+                msg
+            };
+        let owner = mock_info("owner", &[]);
+
+        // anything goes before the sale starts
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            owner.clone(),
+            modify(Some(20), Some("ipfs://revealed"), Update::Keep),
+        )
+        .unwrap();
+
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("buyer", &coins(100, DENOM)),
+            mint_msg(),
+        )
+        .unwrap();
+        assert!(
+            query::get_launch(deps.as_ref(), COLLECTION.to_string())
+                .unwrap()
+                .is_locked
+        );
+
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            owner.clone(),
+            modify(None, Some("ipfs://swapped"), Update::Keep),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::LaunchLocked {}));
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            owner.clone(),
+            modify(Some(30), None, Update::Keep),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::LaunchLocked {}));
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            owner.clone(),
+            modify(None, None, Update::Set(LockPolicy::default())),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::LaunchLocked {}));
+
+        // supply can still shrink
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            owner.clone(),
+            modify(Some(5), None, Update::Keep),
+        )
+        .unwrap();
+
+        let msg = ExecuteMsg::LockLaunch {
+            contract_address: COLLECTION.to_string(),
+        };
+        let err = execute(deps.as_mut(), mock_env(), owner, msg).unwrap_err();
+        assert!(matches!(err, ContractError::AlreadyLocked {}));
+    }
+
+    #[test]
+    fn modify_launch_clears_optional_fields() {
+        let mut deps = mock_dependencies();
+        setup_launch(deps.as_mut());
+
+        let mut msg = modify_launch_msg(COLLECTION);
+        if let ExecuteMsg::ModifyLaunch {
+            public_max_buy,
+            royalty_percentage,
+            royalty_payment_address,
+            ..
+        } = &mut msg
+        {
+            *public_max_buy = Update::Set(2);
+            *royalty_percentage = Update::Set(5);
+            *royalty_payment_address = Update::Set("artist".to_string());
+        }
+        let res = execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), msg).unwrap();
+        assert_eq!(
+            res.attributes[2].value,
+            "public_max_buy,royalty_percentage,royalty_payment_address"
+        );
+
+        let mut msg = modify_launch_msg(COLLECTION);
+        if let ExecuteMsg::ModifyLaunch { public_max_buy, .. } = &mut msg {
+            *public_max_buy = Update::Clear;
+        }
+        // the wire shape of an update, omitted fields are kept
+        let wire: ExecuteMsg = cosmwasm_std::from_slice(
+            br#"{"modify_launch":{
+                "contract_address":"collection",
+                "public_max_buy":"clear",
+                "royalty_percentage":"keep"
+            }}"#,
+        )
+        .unwrap();
+        assert_eq!(wire, msg);
+        let res = execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), msg).unwrap();
+        assert_eq!(res.attributes[2].value, "public_max_buy");
+
+        let launch = query::get_launch(deps.as_ref(), COLLECTION.to_string())
+            .unwrap()
+            .launch;
+        assert_eq!(launch.public_max_buy, None);
+        assert_eq!(launch.royalty_percentage, Some(5));
+        assert_eq!(launch.royalty_payment_address, Some("artist".to_string()));
+    }
+
+    #[test]
+    fn launch_ownership_transfer() {
+        let mut deps = mock_dependencies();
+        setup_launch(deps.as_mut());
+
+        let transfer = |expiry: Option<Expiration>| ExecuteMsg::TransferLaunchOwnership {
+            contract_address: COLLECTION.to_string(),
+            new_owner: "multisig".to_string(),
+            expiry,
+        };
+        let accept = ExecuteMsg::AcceptLaunchOwnership {
+            contract_address: COLLECTION.to_string(),
+        };
+
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("stranger", &[]),
+            transfer(None),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized {}));
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("multisig", &[]),
+            accept.clone(),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::LaunchTransferNotFound {}));
+
+        let expiry = Expiration::AtHeight(mock_env().block.height + 10);
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("owner", &[]),
+            transfer(Some(expiry)),
+        )
+        .unwrap();
+        let ownership = query::get_launch_ownership(deps.as_ref(), COLLECTION.to_string()).unwrap();
+        assert_eq!(ownership.owner, Addr::unchecked("owner"));
+        assert_eq!(ownership.pending_owner, Some(Addr::unchecked("multisig")));
+        assert_eq!(ownership.pending_expiry, Some(expiry));
+
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("stranger", &[]),
+            accept.clone(),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::NotPendingLaunchOwner {}));
+        let mut env = mock_env();
+        env.block.height += 10;
+        let err = execute(
+            deps.as_mut(),
+            env,
+            mock_info("multisig", &[]),
+            accept.clone(),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::LaunchTransferExpired {}));
+
+        // minted tokens carry over to the new owner
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("buyer", &coins(100, DENOM)),
+            mint_msg(),
+        )
+        .unwrap();
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("owner", &[]),
+            transfer(None),
+        )
+        .unwrap();
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("multisig", &[]),
+            accept,
+        )
+        .unwrap();
+
+        let res = query::get_launch(deps.as_ref(), COLLECTION.to_string()).unwrap();
+        assert_eq!(res.launch.owner_address, Addr::unchecked("multisig"));
+        assert_eq!(res.launch.last_token_id, 1);
+        let ownership = query::get_launch_ownership(deps.as_ref(), COLLECTION.to_string()).unwrap();
+        assert_eq!(ownership.pending_owner, None);
+
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("owner", &[]),
+            transfer(None),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized {}));
+    }
+
+    #[test]
+    fn removed_launch_is_archived_and_resumed() {
+        let mut deps = mock_dependencies();
+        setup_launch(deps.as_mut());
+
+        for _ in 0..2 {
+            execute(
+                deps.as_mut(),
+                mock_env(),
+                mock_info("buyer", &coins(100, DENOM)),
+                mint_msg(),
+            )
+            .unwrap();
+        }
+
+        let msg = ExecuteMsg::RemoveLaunch {
+            contract_address: COLLECTION.to_string(),
+            reason: Some("wrong royalties".to_string()),
+        };
+        execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+        assert!(query::get_launch(deps.as_ref(), COLLECTION.to_string()).is_err());
+
+        let archived = query::get_archived_launch(deps.as_ref(), COLLECTION.to_string()).unwrap();
+        assert_eq!(archived.reason, Some("wrong royalties".to_string()));
+        assert_eq!(archived.archived_at, mock_env().block.time.seconds());
+        assert_eq!(archived.launch.last_token_id, 2);
+        let page = query::get_archived_launches(deps.as_ref(), None, None).unwrap();
+        assert_eq!(page.launches, vec![archived.clone()]);
+
+        // re-adding continues after the tokens that were already minted
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("creator", &[]),
+            add_launch_msg(),
+        )
+        .unwrap();
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("buyer", &coins(100, DENOM)),
+            mint_msg(),
+        )
+        .unwrap();
+        assert!(res
+            .attributes
+            .iter()
+            .any(|attr| attr.key == "token_id" && attr.value == "3"));
+        assert_eq!(
+            query::get_archived_launches(deps.as_ref(), None, None)
+                .unwrap()
+                .launches,
+            vec![archived]
+        );
+
+        // renumbering could collide with the ids minted before the removal
+        let msg = ExecuteMsg::RemoveLaunch {
+            contract_address: COLLECTION.to_string(),
+            reason: None,
+        };
+        execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+        let mut msg = add_launch_msg();
+        if let ExecuteMsg::AddLaunch {
+            token_id_scheme, ..
+        } = &mut msg
+        {
+            *token_id_scheme = Some(TokenIdScheme::Sequential {
+                start: 0,
+                prefix: None,
+                padding: None,
+            });
+        }
+        let err = execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap_err();
+        assert!(matches!(err, ContractError::InvalidTokenIdScheme {}));
+
+        // nor does removing a locked launch lift its lock
+        let mut msg = add_launch_msg();
+        if let ExecuteMsg::AddLaunch { max_supply, .. } = &mut msg {
+            *max_supply = Some(20);
+        }
+        let err = execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap_err();
+        assert!(matches!(err, ContractError::LaunchLocked {}));
+        let mut msg = add_launch_msg();
+        if let ExecuteMsg::AddLaunch {
+            provenance_hash, ..
+        } = &mut msg
+        {
+            *provenance_hash = Some("ab".repeat(32));
+        }
+        let err = execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap_err();
+        assert!(matches!(err, ContractError::LaunchLocked {}));
+    }
+
+    #[test]
+    fn airdrop_mint_uses_reserved_supply() {
+        let mut deps = mock_dependencies();
+        setup_launch(deps.as_mut());
+
+        let mut msg = modify_launch_msg(COLLECTION);
+        if let ExecuteMsg::ModifyLaunch {
+            reserved_supply, ..
+        } = &mut msg
+        {
+            *reserved_supply = Update::Set(3);
+        }
+        execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), msg).unwrap();
+
+        let airdrop = |recipients: &[(&str, u32)]| ExecuteMsg::AirdropMint {
+            contract_address: COLLECTION.to_string(),
+            recipients: recipients
+                .iter()
+                .map(|(recipient, quantity)| (recipient.to_string(), *quantity))
+                .collect(),
+        };
+
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("stranger", &[]),
+            airdrop(&[("alice", 2)]),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized {}));
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("owner", &[]),
+            airdrop(&[("alice", 0)]),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::EmptyAirdrop {}));
+
+        // no funds are needed and no fee is taken
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("owner", &[]),
+            airdrop(&[("alice", 2)]),
+        )
+        .unwrap();
+        assert_eq!(res.messages.len(), 2);
+        assert!(res
+            .messages
+            .iter()
+            .all(|msg| matches!(msg.msg, CosmosMsg::Wasm(_))));
+
+        // the reserve can not grow once the launch is locked
+        let mut msg = modify_launch_msg(COLLECTION);
+        if let ExecuteMsg::ModifyLaunch {
+            reserved_supply, ..
+        } = &mut msg
+        {
+            *reserved_supply = Update::Set(8);
+        }
+        let err = execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), msg).unwrap_err();
+        assert!(matches!(err, ContractError::LaunchLocked {}));
+
+        // buyers can not dig into the token left in the reserve
+        for _ in 0..7 {
+            execute(
+                deps.as_mut(),
+                mock_env(),
+                mock_info("buyer", &coins(100, DENOM)),
+                mint_msg(),
+            )
+            .unwrap();
+        }
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("buyer", &coins(100, DENOM)),
+            mint_msg(),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::SoldOut {}));
+
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("creator", &[]),
+            airdrop(&[("bob", 1)]),
+        )
+        .unwrap();
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("owner", &[]),
+            airdrop(&[("bob", 1)]),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::ReservedSupplyExhausted {}));
+
+        let launch = query::get_launch(deps.as_ref(), COLLECTION.to_string())
+            .unwrap()
+            .launch;
+        assert_eq!(launch.last_token_id, 10);
+        assert_eq!(launch.reserved_minted, 3);
+    }
+
+    #[test]
+    fn free_mint_phase() {
+        let mut deps = mock_dependencies();
+        setup_launch(deps.as_mut());
+
+        let mut msg = modify_launch_msg(COLLECTION);
+        if let ExecuteMsg::ModifyLaunch {
+            public_price,
+            public_max_buy,
+            force,
+            ..
+        } = &mut msg
+        {
+            *public_price = Some(coin(0, DENOM));
+            *public_max_buy = Update::Set(2);
+            *force = Some(true);
+        }
+        execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), msg).unwrap();
+
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("buyer", &coins(100, DENOM)),
+            mint_msg(),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::Payment(_)));
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("buyer", &[]),
+            mint_msg(),
+        )
+        .unwrap();
+        assert_eq!(res.messages.len(), 1);
+
+        // the anti-bot fee goes to the taker in full
+        let mut msg = modify_launch_msg(COLLECTION);
+        if let ExecuteMsg::ModifyLaunch { free_mint_fee, .. } = &mut msg {
+            *free_mint_fee = Update::Set(coin(5, DENOM));
+        }
+        execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), msg).unwrap();
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("buyer", &[]),
+            mint_msg(),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::Payment(_)));
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("buyer", &coins(5, DENOM)),
+            mint_msg(),
+        )
+        .unwrap();
+        assert_eq!(
+            res.messages[1].msg,
+            CosmosMsg::Bank(BankMsg::Send {
+                to_address: "taker".to_string(),
+                amount: coins(5, DENOM),
+            })
+        );
+        assert_eq!(res.messages.len(), 2);
+
+        // quotas still apply
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("buyer", &coins(5, DENOM)),
+            mint_msg(),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::MintQuotaExhausted {}));
+    }
+
+    #[test]
+    fn mint_limits_per_tx_block_and_payer() {
+        let mut deps = mock_dependencies();
+        setup_launch(deps.as_mut());
+
+        let mut msg = modify_launch_msg(COLLECTION);
+        if let ExecuteMsg::ModifyLaunch {
+            public_max_buy,
+            mint_limits,
+            ..
+        } = &mut msg
+        {
+            *public_max_buy = Update::Set(3);
+            *mint_limits = Update::Set(MintLimits {
+                max_per_tx: Some(2),
+                max_per_block: Some(3),
+                reject_contract_callers: true,
+            });
+        }
+        execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), msg).unwrap();
+
+        let mint = |receiver: &str, count: u32| {
+            let mut msg = mint_msg();
+            if let ExecuteMsg::Mint {
+                receiver_address,
+                quantity,
+                ..
+            } = &mut msg
+            {
+                *receiver_address = Some(receiver.to_string());
+                *quantity = Some(count);
+            }
+            msg
+        };
+        let next_block = |blocks: u64| {
+            let mut env = mock_env();
+            env.block.height += blocks;
+            env
+        };
+
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("buyer", &coins(300, DENOM)),
+            mint("buyer", 3),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::MintLimitExceeded {}));
+        let err = execute(
+            deps.as_mut(),
+            next_block(5),
+            mock_info("buyer", &coins(100, DENOM)),
+            mint("buyer", 2),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::InsufficientFunds {}));
+
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("buyer", &coins(200, DENOM)),
+            mint("buyer", 2),
+        )
+        .unwrap();
+        let token_ids: Vec<_> = res
+            .attributes
+            .iter()
+            .filter(|attr| attr.key == "token_id")
+            .map(|attr| attr.value.as_str())
+            .collect();
+        assert_eq!(token_ids, vec!["1", "2"]);
+
+        // the block cap is shared by every wallet
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("other", &coins(200, DENOM)),
+            mint("other", 2),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::MintLimitExceeded {}));
+
+        // a fresh receiver does not reset the payer quota
+        let err = execute(
+            deps.as_mut(),
+            next_block(1),
+            mock_info("buyer", &coins(200, DENOM)),
+            mint("friend", 2),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::MintQuotaExhausted {}));
+        execute(
+            deps.as_mut(),
+            next_block(1),
+            mock_info("buyer", &coins(100, DENOM)),
+            mint("friend", 1),
+        )
+        .unwrap();
+
+        deps.querier.update_wasm(|query| match query {
+            WasmQuery::ContractInfo { contract_addr } if contract_addr == "bot" => {
+                SystemResult::Ok(ContractResult::Ok(Binary::from(
+                    br#"{"code_id":1,"creator":"creator","admin":null,"pinned":false,"ibc_port":null}"#,
+                )))
+            }
+            _ => SystemResult::Err(SystemError::UnsupportedRequest {
+                kind: "wasm".to_string(),
+            }),
+        });
+        let err = execute(
+            deps.as_mut(),
+            next_block(2),
+            mock_info("bot", &coins(100, DENOM)),
+            mint("bot", 1),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::ContractCallerRejected {}));
+        execute(
+            deps.as_mut(),
+            next_block(2),
+            mock_info("other", &coins(100, DENOM)),
+            mint("other", 1),
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn holder_gated_public_phase() {
+        let mut deps = mock_dependencies();
+        setup_launch(deps.as_mut());
+
+        let gate_msg = |min_count: u32| {
+            let mut msg = modify_launch_msg(COLLECTION);
+            if let ExecuteMsg::ModifyLaunch {
+                public_holder_gate, ..
+            } = &mut msg
+            {
+                *public_holder_gate = Update::Set(HolderGate {
+                    collection: Addr::unchecked("gate"),
+                    min_count,
+                    require_token_proof: true,
+                    max_mints_per_token: Some(1),
+                });
+            }
+            msg
+        };
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("owner", &[]),
+            gate_msg(0),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::InvalidHolderGate {}));
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("owner", &[]),
+            gate_msg(1),
+        )
+        .unwrap();
+
+        deps.querier.update_wasm(|query| match query {
+            WasmQuery::Smart { contract_addr, msg } if contract_addr == "gate" => {
+                let response = match cosmwasm_std::from_binary(msg).unwrap() {
+                    cw721::Cw721QueryMsg::OwnerOf { token_id, .. } => {
+                        let owner = if token_id == "7" { "buyer" } else { "other" };
+                        to_binary(&OwnerOfResponse {
+                            owner: owner.to_string(),
+                            approvals: vec![],
+                        })
+                    }
+                    cw721::Cw721QueryMsg::Tokens { owner, .. } => to_binary(&TokensResponse {
+                        tokens: if owner == "stranger" {
+                            vec![]
+                        } else {
+                            vec!["7".to_string()]
+                        },
+                    }),
+                    _ => panic!("unexpected query"),
+                };
+                SystemResult::Ok(ContractResult::Ok(response.unwrap()))
+            }
+            _ => SystemResult::Err(SystemError::UnsupportedRequest {
+                kind: "wasm".to_string(),
+            }),
+        });
+
+        let mint = |token: Option<&str>| {
+            let mut msg = mint_msg();
+            if let ExecuteMsg::Mint { gate_token, .. } = &mut msg {
+                *gate_token = token.map(str::to_string);
+            }
+            msg
+        };
+
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("buyer", &coins(100, DENOM)),
+            mint(None),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::MissingGateToken {}));
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("buyer", &coins(100, DENOM)),
+            mint(Some("8")),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::NotHolder {}));
+
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("buyer", &coins(100, DENOM)),
+            mint(Some("7")),
+        )
+        .unwrap();
+
+        // the token is spent, even once it moves to another wallet
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("buyer", &coins(100, DENOM)),
+            mint(Some("7")),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::GateTokenExhausted {}));
+    }
+
+    #[test]
+    fn whitelist_phase_applies_both_gates() {
+        let mut deps = mock_dependencies();
+        setup_launch(deps.as_mut());
+
+        let now = mock_env().block.time.seconds();
+        let mut msg = modify_launch_msg(COLLECTION);
+        if let ExecuteMsg::ModifyLaunch {
+            whitelist_started_at,
+            whitelist_ended_at,
+            public_started_at,
+            public_ended_at,
+            whitelist_holder_gate,
+            whitelist_balance_gate,
+            ..
+        } = &mut msg
+        {
+            *whitelist_started_at = Some(Uint64::new(now - 100));
+            *whitelist_ended_at = Some(Uint64::new(now + 100));
+            *public_started_at = Some(Uint64::new(now + 100));
+            *public_ended_at = Some(Uint64::new(now + 200));
+            *whitelist_holder_gate = Update::Set(HolderGate {
+                collection: Addr::unchecked("gate"),
+                min_count: 1,
+                require_token_proof: false,
+                max_mints_per_token: None,
+            });
+            *whitelist_balance_gate = Update::Set(BalanceGate {
+                source: BalanceSource::Cw20 {
+                    contract: Addr::unchecked("govtoken"),
+                },
+                tiers: vec![BalanceTier {
+                    min_balance: Uint128::new(100),
+                    max_buy: 1,
+                }],
+                snapshot_height: None,
+            });
+        }
+        execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), msg).unwrap();
+
+        // holders own a gate token, whales hold governance tokens too
+        deps.querier.update_wasm(|query| match query {
+            WasmQuery::Smart { contract_addr, msg } if contract_addr == "gate" => {
+                let response = match cosmwasm_std::from_binary(msg).unwrap() {
+                    cw721::Cw721QueryMsg::Tokens { owner, .. } => to_binary(&TokensResponse {
+                        tokens: if owner == "stranger" {
+                            vec![]
+                        } else {
+                            vec!["7".to_string()]
+                        },
+                    }),
+                    _ => panic!("unexpected query"),
+                };
+                SystemResult::Ok(ContractResult::Ok(response.unwrap()))
+            }
+            WasmQuery::Smart { contract_addr, msg } if contract_addr == "govtoken" => {
+                let balance = match cosmwasm_std::from_binary(msg).unwrap() {
+                    BalanceGateQueryMsg::Balance { address } if address != "holder" => 500u128,
+                    _ => 0,
+                };
+                SystemResult::Ok(ContractResult::Ok(
+                    to_binary(&BalanceResponse {
+                        balance: Uint128::new(balance),
+                    })
+                    .unwrap(),
+                ))
+            }
+            _ => SystemResult::Err(SystemError::UnsupportedRequest {
+                kind: "wasm".to_string(),
+            }),
+        });
+
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("stranger", &coins(50, DENOM)),
+            mint_msg(),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::NotHolder {}));
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("holder", &coins(50, DENOM)),
+            mint_msg(),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::InsufficientBalance {}));
+
+        // the balance tier sets the whitelist quota
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("whale", &coins(50, DENOM)),
+            mint_msg(),
+        )
+        .unwrap();
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("whale", &coins(50, DENOM)),
+            mint_msg(),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::MintQuotaExhausted {}));
+    }
+
+    #[test]
+    fn trade_in_mint() {
+        let mut deps = mock_dependencies();
+        setup_launch(deps.as_mut());
+
+        let mut msg = modify_launch_msg(COLLECTION);
+        if let ExecuteMsg::ModifyLaunch { trade_in, .. } = &mut msg {
+            *trade_in = Update::Set(TradeIn {
+                collection: Addr::unchecked("season1"),
+                sink: None,
+            });
+        }
+        execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), msg).unwrap();
+
+        let trade_in = |token_id: &str| {
+            ExecuteMsg::ReceiveNft(Cw721ReceiveMsg {
+                sender: "holder".to_string(),
+                token_id: token_id.to_string(),
+                msg: to_binary(&ReceiveNftMsg::TradeIn {
+                    contract_address: COLLECTION.to_string(),
+                    receiver_address: None,
+                    gate_token: None,
+                })
+                .unwrap(),
+            })
+        };
+
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("season2", &[]),
+            trade_in("5"),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::TradeInNotAccepted {}));
+        // the public price has to be prepaid
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("season1", &[]),
+            trade_in("5"),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::InsufficientFunds {}));
+
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("holder", &coins(150, DENOM)),
+            ExecuteMsg::DepositTradeInFunds {
+                contract_address: COLLECTION.to_string(),
+            },
+        )
+        .unwrap();
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("season1", &[]),
+            trade_in("5"),
+        )
+        .unwrap();
+        assert_eq!(
+            res.messages[0].msg,
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "season1".to_string(),
+                msg: to_binary(&cw721::Cw721ExecuteMsg::Burn {
+                    token_id: "5".to_string(),
+                })
+                .unwrap(),
+                funds: vec![],
+            })
+        );
+        assert!(res
+            .attributes
+            .iter()
+            .any(|attr| attr.key == "token_id" && attr.value == "1"));
+        assert!(res
+            .attributes
+            .iter()
+            .any(|attr| attr.key == "receiver_address" && attr.value == "holder"));
+
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("season1", &[]),
+            trade_in("6"),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::InsufficientFunds {}));
+
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("holder", &[]),
+            ExecuteMsg::WithdrawTradeInFunds {
+                contract_address: COLLECTION.to_string(),
+            },
+        )
+        .unwrap();
+        assert_eq!(
+            res.messages[0].msg,
+            BankMsg::Send {
+                to_address: "holder".to_string(),
+                amount: coins(50, DENOM),
+            }
+            .into()
+        );
+
+        // a traded token does not get around the holder gate of the phase
+        let mut msg = modify_launch_msg(COLLECTION);
+        if let ExecuteMsg::ModifyLaunch {
+            public_holder_gate, ..
+        } = &mut msg
+        {
+            *public_holder_gate = Update::Set(HolderGate {
+                collection: Addr::unchecked("gate"),
+                min_count: 1,
+                require_token_proof: false,
+                max_mints_per_token: None,
+            });
+        }
+        execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), msg).unwrap();
+        deps.querier.update_wasm(|query| match query {
+            WasmQuery::Smart { contract_addr, .. } if contract_addr == "gate" => SystemResult::Ok(
+                ContractResult::Ok(to_binary(&TokensResponse { tokens: vec![] }).unwrap()),
+            ),
+            _ => SystemResult::Err(SystemError::UnsupportedRequest {
+                kind: "wasm".to_string(),
+            }),
+        });
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("season1", &[]),
+            trade_in("6"),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::NotHolder {}));
+    }
+
+    #[test]
+    fn balance_gated_public_quota() {
+        let mut deps = mock_dependencies();
+        setup_launch(deps.as_mut());
+
+        let gate_msg = |tiers: &[(u128, u32)]| {
+            let mut msg = modify_launch_msg(COLLECTION);
+            if let ExecuteMsg::ModifyLaunch {
+                public_max_buy,
+                public_balance_gate,
+                ..
+            } = &mut msg
+            {
+                *public_max_buy = Update::Set(10);
+                *public_balance_gate = Update::Set(BalanceGate {
+                    source: BalanceSource::Cw20 {
+                        contract: Addr::unchecked("govtoken"),
+                    },
+                    tiers: tiers
+                        .iter()
+                        .map(|(min_balance, max_buy)| BalanceTier {
+                            min_balance: Uint128::new(*min_balance),
+                            max_buy: *max_buy,
+                        })
+                        .collect(),
+                    snapshot_height: None,
+                });
+            }
+            msg
+        };
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("owner", &[]),
+            gate_msg(&[(1000, 3), (100, 1)]),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::InvalidBalanceGate {}));
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("owner", &[]),
+            gate_msg(&[(100, 1), (1000, 3)]),
+        )
+        .unwrap();
+
+        deps.querier.update_wasm(|query| match query {
+            WasmQuery::Smart { contract_addr, msg } if contract_addr == "govtoken" => {
+                let balance = match cosmwasm_std::from_binary(msg).unwrap() {
+                    BalanceGateQueryMsg::Balance { address } if address == "whale" => 5000u128,
+                    BalanceGateQueryMsg::Balance { address } if address == "buyer" => 500,
+                    _ => 0,
+                };
+                SystemResult::Ok(ContractResult::Ok(
+                    to_binary(&BalanceResponse {
+                        balance: Uint128::new(balance),
+                    })
+                    .unwrap(),
+                ))
+            }
+            _ => SystemResult::Err(SystemError::UnsupportedRequest {
+                kind: "wasm".to_string(),
+            }),
+        });
+
+        let mint = |count: u32| {
+            let mut msg = mint_msg();
+            if let ExecuteMsg::Mint { quantity, .. } = &mut msg {
+                *quantity = Some(count);
+            }
+            msg
+        };
+
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("stranger", &coins(100, DENOM)),
+            mint(1),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::InsufficientBalance {}));
+
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("buyer", &coins(100, DENOM)),
+            mint(1),
+        )
+        .unwrap();
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("buyer", &coins(100, DENOM)),
+            mint(1),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::MintQuotaExhausted {}));
+
+        // the tier quota replaces the flat public_max_buy
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("whale", &coins(300, DENOM)),
+            mint(3),
+        )
+        .unwrap();
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("whale", &coins(100, DENOM)),
+            mint(1),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::MintQuotaExhausted {}));
+    }
+
+    #[test]
+    fn staking_balance_gate_reads_snapshot() {
+        let mut deps = mock_dependencies();
+        setup_launch(deps.as_mut());
+
+        let height = mock_env().block.height;
+        let staking = BalanceSource::Staking {
+            contract: Addr::unchecked("govtoken"),
+        };
+        let cw20 = BalanceSource::Cw20 {
+            contract: Addr::unchecked("govtoken"),
+        };
+        let gate_msg = |source: &BalanceSource, snapshot_height: Option<u64>| {
+            let mut msg = modify_launch_msg(COLLECTION);
+            if let ExecuteMsg::ModifyLaunch {
+                public_balance_gate,
+                ..
+            } = &mut msg
+            {
+                *public_balance_gate = Update::Set(BalanceGate {
+                    source: source.clone(),
+                    tiers: vec![BalanceTier {
+                        min_balance: Uint128::new(100),
+                        max_buy: 2,
+                    }],
+                    snapshot_height,
+                });
+            }
+            msg
+        };
+
+        // staking needs a past snapshot, cw20 balances cannot be snapshotted
+        for msg in [
+            gate_msg(&staking, None),
+            gate_msg(&staking, Some(height + 1)),
+            gate_msg(&cw20, Some(height - 10)),
+        ] {
+            let err = execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), msg).unwrap_err();
+            assert!(matches!(err, ContractError::InvalidBalanceGate {}));
+        }
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("owner", &[]),
+            gate_msg(&staking, Some(height - 10)),
+        )
+        .unwrap();
+
+        let snapshot_height = height - 10;
+        deps.querier.update_wasm(move |query| match query {
+            WasmQuery::Smart { contract_addr, msg } if contract_addr == "govtoken" => {
+                let balance = match cosmwasm_std::from_binary(msg).unwrap() {
+                    BalanceGateQueryMsg::StakedBalanceAtHeight { address, height }
+                        if address == "staker" && height == Some(snapshot_height) =>
+                    {
+                        500u128
+                    }
+                    _ => 0,
+                };
+                SystemResult::Ok(ContractResult::Ok(
+                    to_binary(&StakedBalanceAtHeightResponse {
+                        balance: Uint128::new(balance),
+                        height: snapshot_height,
+                    })
+                    .unwrap(),
+                ))
+            }
+            _ => SystemResult::Err(SystemError::UnsupportedRequest {
+                kind: "wasm".to_string(),
+            }),
+        });
+
+        let mut msg = mint_msg();
+        if let ExecuteMsg::Mint { quantity, .. } = &mut msg {
+            *quantity = Some(2);
+        }
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("staker", &coins(200, DENOM)),
+            msg,
+        )
+        .unwrap();
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("stranger", &coins(100, DENOM)),
+            mint_msg(),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::InsufficientBalance {}));
+    }
+
+    #[test]
+    fn raffle_registration_draw_and_claims() {
+        let mut deps = mock_dependencies();
+        setup_launch(deps.as_mut());
+
+        let now = mock_env().block.time.seconds();
+        let commitment = hex::encode(sha2::Sha256::digest(b"secret"));
+        let mut msg = modify_launch_msg(COLLECTION);
+        if let ExecuteMsg::ModifyLaunch { raffle, .. } = &mut msg {
+            *raffle = Update::Set(RaffleConfig {
+                registration_started_at: now - 50,
+                registration_ended_at: now + 50,
+                draw_deadline: now + 150,
+                winners: 2,
+                seed_commitment: Some(commitment),
+            });
+        }
+        execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), msg).unwrap();
+
+        // registration is open, nobody entered yet but the terms are fixed
+        let mut msg = modify_launch_msg(COLLECTION);
+        if let ExecuteMsg::ModifyLaunch { raffle, .. } = &mut msg {
+            *raffle = Update::Clear;
+        }
+        let err = execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), msg).unwrap_err();
+        assert!(matches!(err, ContractError::PhaseActive {}));
+
+        let registrants = ["alice", "bob", "carol"];
+        for registrant in registrants {
+            execute(
+                deps.as_mut(),
+                mock_env(),
+                mock_info(registrant, &coins(100, DENOM)),
+                ExecuteMsg::Register {
+                    contract_address: COLLECTION.to_string(),
+                },
+            )
+            .unwrap();
+        }
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("alice", &coins(100, DENOM)),
+            ExecuteMsg::Register {
+                contract_address: COLLECTION.to_string(),
+            },
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::AlreadyRegistered {}));
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("dave", &coins(100, DENOM)),
+            mint_msg(),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::Closed {}));
+
+        let draw = |seed: &str| ExecuteMsg::DrawRaffle {
+            contract_address: COLLECTION.to_string(),
+            seed: Some(seed.to_string()),
+        };
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("owner", &[]),
+            draw("secret"),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::RaffleClosed {}));
+
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(60);
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("owner", &[]),
+            draw("guess"),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::InvalidSeed {}));
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("owner", &[]),
+            draw("secret"),
+        )
+        .unwrap();
+
+        // the launch stays until every deposit is claimed or withdrawn
+        let remove = || ExecuteMsg::RemoveLaunch {
+            contract_address: COLLECTION.to_string(),
+            reason: None,
+        };
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("creator", &[]),
+            remove(),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::RaffleInProgress {}));
+
+        let mut winners = 0;
+        for registrant in registrants {
+            let raffle: RaffleResponse = cosmwasm_std::from_binary(
+                &query(
+                    deps.as_ref(),
+                    env.clone(),
+                    QueryMsg::GetRaffle {
+                        contract_address: COLLECTION.to_string(),
+                        address: Some(registrant.to_string()),
+                    },
+                )
+                .unwrap(),
+            )
+            .unwrap();
+            let claim = ExecuteMsg::ClaimRaffleMint {
+                contract_address: COLLECTION.to_string(),
+            };
+            let withdraw = ExecuteMsg::WithdrawRaffleDeposit {
+                contract_address: COLLECTION.to_string(),
+            };
+
+            if raffle.entry.unwrap().won.unwrap() {
+                winners += 1;
+                let err = execute(
+                    deps.as_mut(),
+                    env.clone(),
+                    mock_info(registrant, &[]),
+                    withdraw,
+                )
+                .unwrap_err();
+                assert!(matches!(err, ContractError::NothingToWithdraw {}));
+                let res = execute(
+                    deps.as_mut(),
+                    env.clone(),
+                    mock_info(registrant, &[]),
+                    claim,
+                )
+                .unwrap();
+                assert!(res
+                    .attributes
+                    .iter()
+                    .any(|attr| attr.key == "token_id" && attr.value == winners.to_string()));
+            } else {
+                let err = execute(
+                    deps.as_mut(),
+                    env.clone(),
+                    mock_info(registrant, &[]),
+                    claim,
+                )
+                .unwrap_err();
+                assert!(matches!(err, ContractError::NotRaffleWinner {}));
+                let res = execute(
+                    deps.as_mut(),
+                    env.clone(),
+                    mock_info(registrant, &[]),
+                    withdraw,
+                )
+                .unwrap();
+                assert_eq!(
+                    res.messages[0].msg,
+                    BankMsg::Send {
+                        to_address: registrant.to_string(),
+                        amount: coins(100, DENOM),
+                    }
+                    .into()
+                );
+            }
+        }
+        assert_eq!(winners, 2);
+        execute(deps.as_mut(), env, mock_info("creator", &[]), remove()).unwrap();
+    }
+
+    #[test]
+    fn undrawn_raffle_releases_deposits() {
+        let mut deps = mock_dependencies();
+        setup_launch(deps.as_mut());
+
+        let now = mock_env().block.time.seconds();
+        let raffle_msg = |draw_deadline: u64| {
+            let mut msg = modify_launch_msg(COLLECTION);
+            if let ExecuteMsg::ModifyLaunch { raffle, .. } = &mut msg {
+                *raffle = Update::Set(RaffleConfig {
+                    registration_started_at: now - 50,
+                    registration_ended_at: now + 50,
+                    draw_deadline,
+                    winners: 1,
+                    seed_commitment: None,
+                });
+            }
+            msg
+        };
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("owner", &[]),
+            raffle_msg(now + 50),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::InvalidRaffle {}));
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("owner", &[]),
+            raffle_msg(now + 150),
+        )
+        .unwrap();
+
+        let registrants = ["alice", "bob"];
+        for registrant in registrants {
+            execute(
+                deps.as_mut(),
+                mock_env(),
+                mock_info(registrant, &coins(100, DENOM)),
+                ExecuteMsg::Register {
+                    contract_address: COLLECTION.to_string(),
+                },
+            )
+            .unwrap();
+        }
+        let withdraw = ExecuteMsg::WithdrawRaffleDeposit {
+            contract_address: COLLECTION.to_string(),
+        };
+
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(100);
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("alice", &[]),
+            withdraw.clone(),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::RaffleNotDrawn {}));
+
+        env.block.time = env.block.time.plus_seconds(50);
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("owner", &[]),
+            ExecuteMsg::DrawRaffle {
+                contract_address: COLLECTION.to_string(),
+                seed: None,
+            },
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::RaffleExpired {}));
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("alice", &[]),
+            ExecuteMsg::ClaimRaffleMint {
+                contract_address: COLLECTION.to_string(),
+            },
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::NotRaffleWinner {}));
+
+        for registrant in registrants {
+            let res = execute(
+                deps.as_mut(),
+                env.clone(),
+                mock_info(registrant, &[]),
+                withdraw.clone(),
+            )
+            .unwrap();
+            assert_eq!(
+                res.messages[0].msg,
+                BankMsg::Send {
+                    to_address: registrant.to_string(),
+                    amount: coins(100, DENOM),
+                }
+                .into()
+            );
+        }
+    }
+
+    #[test]
+    fn open_edition_is_finalized_after_the_sale() {
+        let mut deps = mock_dependencies();
+        setup_launch(deps.as_mut());
+
+        let open_edition = |is_static: Option<bool>| {
+            let mut msg = modify_launch_msg(COLLECTION);
+            if let ExecuteMsg::ModifyLaunch {
+                max_supply,
+                is_base_uri_static,
+                ..
+            } = &mut msg
+            {
+                *max_supply = Update::Clear;
+                *is_base_uri_static = is_static;
+            }
+            msg
+        };
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("owner", &[]),
+            open_edition(None),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::InvalidOpenEdition {}));
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("owner", &[]),
+            open_edition(Some(true)),
+        )
+        .unwrap();
+
+        // more than the former cap of 10
+        let mut msg = mint_msg();
+        if let ExecuteMsg::Mint { quantity, .. } = &mut msg {
+            *quantity = Some(20);
+        }
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("buyer", &coins(2000, DENOM)),
+            msg,
+        )
+        .unwrap();
+        assert!(res
+            .attributes
+            .iter()
+            .any(|attr| attr.key == "token_id" && attr.value == "20"));
+
+        let finalize = || ExecuteMsg::FinalizeLaunch {
+            contract_address: COLLECTION.to_string(),
+        };
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("anyone", &[]),
+            finalize(),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::SaleNotEnded {}));
+
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(200);
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("anyone", &[]),
+            finalize(),
+        )
+        .unwrap();
+        let launch: LaunchResponse = cosmwasm_std::from_binary(
+            &query(
+                deps.as_ref(),
+                env.clone(),
+                QueryMsg::GetLaunch {
+                    contract_address: COLLECTION.to_string(),
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(launch.launch.edition_size, Some(20));
+
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("anyone", &[]),
+            finalize(),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::LaunchFinalized {}));
+        let mut msg = modify_launch_msg(COLLECTION);
+        if let ExecuteMsg::ModifyLaunch {
+            public_ended_at, ..
+        } = &mut msg
+        {
+            *public_ended_at = Some(Uint64::new(9999999999));
+        }
+        let err = execute(deps.as_mut(), env, mock_info("owner", &[]), msg).unwrap_err();
+        assert!(matches!(err, ContractError::LaunchFinalized {}));
+    }
+
+    #[test]
+    fn tiered_mint_and_status() {
+        let mut deps = mock_dependencies();
+        setup_launch(deps.as_mut());
+
+        let tier = |name: &str, supply: u64, price: u128| Tier {
+            name: name.to_string(),
+            supply,
+            price: coin(price, DENOM),
+            token_id_prefix: format!("{}-", name),
+            uri_template: format!("ipfs://{}/{{id}}.json", name),
+        };
+        let set_tiers = |tiers: Vec<Tier>, force: bool| {
+            let mut msg = modify_launch_msg(COLLECTION);
+            if let ExecuteMsg::ModifyLaunch {
+                tiers: msg_tiers,
+                force: msg_force,
+                ..
+            } = &mut msg
+            {
+                *msg_tiers = Update::Set(tiers);
+                *msg_force = Some(force);
+            }
+            msg
+        };
+
+        let mut msg = modify_launch_msg(COLLECTION);
+        if let ExecuteMsg::ModifyLaunch { lock_policy, .. } = &mut msg {
+            *lock_policy = Update::Set(LockPolicy {
+                freeze_prices: true,
+                ..LockPolicy::default()
+            });
+        }
+        execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), msg).unwrap();
+
+        // tier prices of the live public phase only move when forced
+        let tiers = vec![tier("gold", 2, 300), tier("silver", 8, 100)];
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("owner", &[]),
+            set_tiers(tiers.clone(), false),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::PhaseActive {}));
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("owner", &[]),
+            set_tiers(tiers, true),
+        )
+        .unwrap();
+
+        // tier supplies can not exceed the launch supply
+        let mut msg = modify_launch_msg(COLLECTION);
+        if let ExecuteMsg::ModifyLaunch { max_supply, .. } = &mut msg {
+            *max_supply = Update::Set(9);
+        }
+        let err = execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), msg).unwrap_err();
+        assert!(matches!(err, ContractError::InvalidTiers {}));
+
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("buyer", &coins(100, DENOM)),
+            mint_msg(),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::UnknownTier {}));
+
+        let tier_mint = |name: &str, count: u32| {
+            let mut msg = mint_msg();
+            if let ExecuteMsg::Mint { quantity, tier, .. } = &mut msg {
+                *quantity = Some(count);
+                *tier = Some(name.to_string());
+            }
+            msg
+        };
+
+        // the tier price replaces the public price
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("buyer", &coins(200, DENOM)),
+            tier_mint("gold", 2),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::InsufficientFunds {}));
+
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("buyer", &coins(600, DENOM)),
+            tier_mint("gold", 2),
+        )
+        .unwrap();
+        let token_ids: Vec<_> = res
+            .attributes
+            .iter()
+            .filter(|attr| attr.key == "token_id")
+            .map(|attr| attr.value.as_str())
+            .collect();
+        assert_eq!(token_ids, ["gold-1", "gold-2"]);
+        assert!(res.messages.iter().any(|msg| matches!(
+            &msg.msg,
+            CosmosMsg::Wasm(WasmMsg::Execute { msg, .. })
+                if String::from_utf8_lossy(msg).contains("ipfs://gold/2.json")
+        )));
+
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("buyer", &coins(300, DENOM)),
+            tier_mint("gold", 1),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::SoldOut {}));
+
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("buyer", &coins(100, DENOM)),
+            tier_mint("silver", 1),
+        )
+        .unwrap();
+
+        let status: LaunchStatus = cosmwasm_std::from_binary(
+            &query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::GetLaunchStatus {
+                    contract_address: COLLECTION.to_string(),
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(
+            status
+                .tiers
+                .iter()
+                .map(|tier| (tier.name.as_str(), tier.remaining))
+                .collect::<Vec<_>>(),
+            [("gold", 0), ("silver", 7)]
+        );
+
+        // the lock freezes tier prices, even when forced
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("owner", &[]),
+            set_tiers(vec![tier("gold", 2, 300), tier("silver", 8, 50)], true),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::LaunchLocked {}));
+    }
+
+    #[test]
+    fn tiers_exclude_untiered_mints() {
+        let mut deps = mock_dependencies();
+        setup_launch(deps.as_mut());
+
+        let now = mock_env().block.time.seconds();
+        let tiers = vec![Tier {
+            name: "gold".to_string(),
+            supply: 4,
+            price: coin(300, DENOM),
+            token_id_prefix: "gold-".to_string(),
+            uri_template: "ipfs://gold/{id}.json".to_string(),
+        }];
+        let raffle = RaffleConfig {
+            registration_started_at: now + 50,
+            registration_ended_at: now + 150,
+            draw_deadline: now + 250,
+            winners: 1,
+            seed_commitment: None,
+        };
+        let trade_in = TradeIn {
+            collection: Addr::unchecked("season1"),
+            sink: None,
+        };
+
+        // raffle claims, trade-ins and airdrops could never be minted
+        for untiered in 0..3 {
+            let mut msg = modify_launch_msg(COLLECTION);
+            if let ExecuteMsg::ModifyLaunch {
+                tiers: msg_tiers,
+                raffle: msg_raffle,
+                trade_in: msg_trade_in,
+                reserved_supply,
+                force,
+                ..
+            } = &mut msg
+            {
+                *force = Some(true);
+                *msg_tiers = Update::Set(tiers.clone());
+                match untiered {
+                    0 => *msg_raffle = Update::Set(raffle.clone()),
+                    1 => *msg_trade_in = Update::Set(trade_in.clone()),
+                    _ => *reserved_supply = Update::Set(2),
+                }
+            }
+            let err = execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), msg).unwrap_err();
+            assert!(matches!(err, ContractError::InvalidTiers {}));
+
+            let mut msg = add_launch_msg();
+            if let ExecuteMsg::AddLaunch {
+                contract_address,
+                tiers: msg_tiers,
+                raffle: msg_raffle,
+                trade_in: msg_trade_in,
+                reserved_supply,
+                ..
+            } = &mut msg
+            {
+                *contract_address = "season2".to_string();
+                *msg_tiers = Some(tiers.clone());
+                match untiered {
+                    0 => *msg_raffle = Some(raffle.clone()),
+                    1 => *msg_trade_in = Some(trade_in.clone()),
+                    _ => *reserved_supply = Some(2),
+                }
+            }
+            let err =
+                execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap_err();
+            assert!(matches!(err, ContractError::InvalidTiers {}));
+        }
+
+        // and tiers can not be added to a launch that has one
+        let mut msg = modify_launch_msg(COLLECTION);
+        if let ExecuteMsg::ModifyLaunch {
+            raffle: msg_raffle, ..
+        } = &mut msg
+        {
+            *msg_raffle = Update::Set(raffle);
+        }
+        execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), msg).unwrap();
+        let mut msg = modify_launch_msg(COLLECTION);
+        if let ExecuteMsg::ModifyLaunch {
+            tiers: msg_tiers,
+            force,
+            ..
+        } = &mut msg
+        {
+            *msg_tiers = Update::Set(tiers);
+            *force = Some(true);
+        }
+        let err = execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), msg).unwrap_err();
+        assert!(matches!(err, ContractError::InvalidTiers {}));
+    }
+
+    #[test]
+    fn uri_template_mint_and_preview() {
+        let mut deps = mock_dependencies();
+        setup_launch(deps.as_mut());
+
+        let set_template = |template: &str| {
+            let mut msg = modify_launch_msg(COLLECTION);
+            if let ExecuteMsg::ModifyLaunch { uri_template, .. } = &mut msg {
+                *uri_template = Update::Set(template.to_string());
+            }
+            msg
+        };
+        for uri_template in ["ipfs://cid/{token}", "ipfs://cid/{tier}/{id}"] {
+            let err = execute(
+                deps.as_mut(),
+                mock_env(),
+                mock_info("owner", &[]),
+                set_template(uri_template),
+            )
+            .unwrap_err();
+            assert!(matches!(err, ContractError::InvalidUriTemplate {}));
+        }
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("owner", &[]),
+            set_template("ipfs://cid/{id:03}-{shuffled_id}"),
+        )
+        .unwrap();
+
+        let preview = |deps: Deps, number: u64| -> TokenUriResponse {
+            cosmwasm_std::from_binary(
+                &query(
+                    deps,
+                    mock_env(),
+                    QueryMsg::PreviewTokenUri {
+                        contract_address: COLLECTION.to_string(),
+                        number,
+                        tier: None,
+                    },
+                )
+                .unwrap(),
+            )
+            .unwrap()
+        };
+        let first = preview(deps.as_ref(), 1);
+        assert_eq!(first.token_id, "1");
+        assert!(first.token_uri.starts_with("ipfs://cid/001-"));
+        let err = query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::PreviewTokenUri {
+                contract_address: COLLECTION.to_string(),
+                number: 0,
+                tier: None,
+            },
+        )
+        .unwrap_err();
+        assert!(err
+            .to_string()
+            .contains(&ContractError::InvalidQuantity {}.to_string()));
+
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("buyer", &coins(100, DENOM)),
+            mint_msg(),
+        )
+        .unwrap();
+        assert!(res.messages.iter().any(|msg| matches!(
+            &msg.msg,
+            CosmosMsg::Wasm(WasmMsg::Execute { msg, .. })
+                if String::from_utf8_lossy(msg).contains(&first.token_uri)
+        )));
+
+        // the shuffle is over max_supply, which the lock now freezes
+        let mut msg = modify_launch_msg(COLLECTION);
+        if let ExecuteMsg::ModifyLaunch { max_supply, .. } = &mut msg {
+            *max_supply = Update::Set(9);
+        }
+        let err = execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), msg).unwrap_err();
+        assert!(matches!(err, ContractError::LaunchLocked {}));
+    }
+
+    #[test]
+    fn token_id_schemes() {
+        let mut deps = mock_dependencies();
+        setup_launch(deps.as_mut());
+
+        let set_scheme = |scheme: Update<TokenIdScheme>| {
+            let mut msg = modify_launch_msg(COLLECTION);
+            if let ExecuteMsg::ModifyLaunch {
+                token_id_scheme, ..
+            } = &mut msg
+            {
+                *token_id_scheme = scheme;
+            }
+            msg
+        };
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("owner", &[]),
+            set_scheme(Update::Set(TokenIdScheme::Sequential {
+                start: 0,
+                prefix: Some("genesis-".to_string()),
+                padding: Some(4),
+            })),
+        )
+        .unwrap();
+
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("buyer", &coins(100, DENOM)),
+            mint_msg(),
+        )
+        .unwrap();
+        assert!(res
+            .attributes
+            .iter()
+            .any(|attr| attr.key == "token_id" && attr.value == "genesis-0000"));
+        assert!(res.messages.iter().any(|msg| matches!(
+            &msg.msg,
+            CosmosMsg::Wasm(WasmMsg::Execute { msg, .. })
+                if String::from_utf8_lossy(msg).contains("ipfs://base/genesis-0000.png")
+        )));
+
+        // minted ids can not be renumbered
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("owner", &[]),
+            set_scheme(Update::Clear),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::LaunchLocked {}));
+
+        let mut deps = mock_dependencies();
+        setup_launch(deps.as_mut());
+        let add_token_ids = |token_ids: &[&str]| ExecuteMsg::AddTokenIds {
+            contract_address: COLLECTION.to_string(),
+            token_ids: token_ids.iter().map(|id| id.to_string()).collect(),
+        };
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("owner", &[]),
+            add_token_ids(&["a"]),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::InvalidTokenIdScheme {}));
+
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("owner", &[]),
+            set_scheme(Update::Set(TokenIdScheme::List {})),
+        )
+        .unwrap();
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("owner", &[]),
+            add_token_ids(&["dragon", "phoenix"]),
+        )
+        .unwrap();
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("owner", &[]),
+            add_token_ids(&["phoenix"]),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::DuplicateTokenId {}));
+
+        let mint = |count: u32| {
+            let mut msg = mint_msg();
+            if let ExecuteMsg::Mint { quantity, .. } = &mut msg {
+                *quantity = Some(count);
+            }
+            msg
+        };
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("buyer", &coins(200, DENOM)),
+            mint(2),
+        )
+        .unwrap();
+        let token_ids: Vec<_> = res
+            .attributes
+            .iter()
+            .filter(|attr| attr.key == "token_id")
+            .map(|attr| attr.value.as_str())
+            .collect();
+        assert_eq!(token_ids, ["dragon", "phoenix"]);
+
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("buyer", &coins(100, DENOM)),
+            mint(1),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::TokenIdsExhausted {}));
+
+        // ids past u64::MAX run out instead of wrapping
+        let mut deps = mock_dependencies();
+        setup_launch(deps.as_mut());
+        let mut msg = set_scheme(Update::Set(TokenIdScheme::Sequential {
+            start: u64::MAX,
+            prefix: None,
+            padding: None,
+        }));
+        if let ExecuteMsg::ModifyLaunch { uri_template, .. } = &mut msg {
+            *uri_template = Update::Set("ipfs://cid/{id}.json".to_string());
+        }
+        execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), msg).unwrap();
+        let preview = |deps: Deps, number: u64| {
+            query(
+                deps,
+                mock_env(),
+                QueryMsg::PreviewTokenUri {
+                    contract_address: COLLECTION.to_string(),
+                    number,
+                    tier: None,
+                },
+            )
+        };
+        let last: TokenUriResponse =
+            cosmwasm_std::from_binary(&preview(deps.as_ref(), 1).unwrap()).unwrap();
+        assert_eq!(last.token_id, u64::MAX.to_string());
+        assert_eq!(last.token_uri, format!("ipfs://cid/{}.json", u64::MAX));
+        let err = preview(deps.as_ref(), 2).unwrap_err();
+        assert!(err
+            .to_string()
+            .contains(&ContractError::TokenIdsExhausted {}.to_string()));
+
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("buyer", &coins(100, DENOM)),
+            mint(1),
+        )
+        .unwrap();
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("buyer", &coins(100, DENOM)),
+            mint(1),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::TokenIdsExhausted {}));
+    }
+
+    #[test]
+    fn provenance_starting_index() {
+        let mut deps = mock_dependencies();
+        setup_launch(deps.as_mut());
+
+        let set_provenance = |hash: &str| {
+            let mut msg = modify_launch_msg(COLLECTION);
+            if let ExecuteMsg::ModifyLaunch {
+                provenance_hash, ..
+            } = &mut msg
+            {
+                *provenance_hash = Update::Set(hash.to_string());
+            }
+            msg
+        };
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("owner", &[]),
+            set_provenance("abc"),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::InvalidProvenanceHash {}));
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("owner", &[]),
+            set_provenance(&"ab".repeat(32)),
+        )
+        .unwrap();
+
+        let set_starting_index = || ExecuteMsg::SetStartingIndex {
+            contract_address: COLLECTION.to_string(),
+        };
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("anyone", &[]),
+            set_starting_index(),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::SaleNotEnded {}));
+
+        let provenance = |deps: Deps| -> ProvenanceResponse {
+            cosmwasm_std::from_binary(
+                &query(
+                    deps,
+                    mock_env(),
+                    QueryMsg::GetProvenance {
+                        contract_address: COLLECTION.to_string(),
+                    },
+                )
+                .unwrap(),
+            )
+            .unwrap()
+        };
+
+        let mut mint = mint_msg();
+        if let ExecuteMsg::Mint {
+            ref mut quantity, ..
+        } = mint
+        {
+            *quantity = Some(9);
+        }
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("buyer", &coins(900, DENOM)),
+            mint,
+        )
+        .unwrap();
+        assert_eq!(provenance(deps.as_ref()).starting_index, None);
+
+        // the hash is frozen from the first mint on
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("owner", &[]),
+            set_provenance(&"cd".repeat(32)),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::LaunchLocked {}));
+
+        // the last token sells the launch out
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("buyer", &coins(100, DENOM)),
+            mint_msg(),
+        )
+        .unwrap();
+        let res = provenance(deps.as_ref());
+        assert_eq!(res.provenance_hash, Some("ab".repeat(32)));
+        assert!(res.starting_index.unwrap() < 10);
+
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(200);
+        let err = execute(
+            deps.as_mut(),
+            env,
+            mock_info("anyone", &[]),
+            set_starting_index(),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::StartingIndexSet {}));
+
+        // open editions draw the index when they are finalized
+        let mut deps = mock_dependencies();
+        setup_launch(deps.as_mut());
+        let mut msg = modify_launch_msg(COLLECTION);
+        if let ExecuteMsg::ModifyLaunch {
+            max_supply,
+            is_base_uri_static,
+            ..
+        } = &mut msg
+        {
+            *max_supply = Update::Clear;
+            *is_base_uri_static = Some(true);
+        }
+        execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), msg).unwrap();
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("owner", &[]),
+            set_provenance(&"ab".repeat(32)),
+        )
+        .unwrap();
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("buyer", &coins(100, DENOM)),
+            mint_msg(),
+        )
+        .unwrap();
+
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(200);
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("anyone", &[]),
+            set_starting_index(),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::InvalidOpenEdition {}));
+        execute(
+            deps.as_mut(),
+            env,
+            mock_info("anyone", &[]),
+            ExecuteMsg::FinalizeLaunch {
+                contract_address: COLLECTION.to_string(),
+            },
+        )
+        .unwrap();
+        assert_eq!(provenance(deps.as_ref()).starting_index, Some(0));
     }
 }
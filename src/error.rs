@@ -1,5 +1,6 @@
-use cosmwasm_std::StdError;
+use cosmwasm_std::{StdError, VerificationError};
 use cw_ownable::OwnershipError;
+use cw_utils::PaymentError;
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -13,6 +14,12 @@ pub enum ContractError {
     #[error(transparent)]
     Ownership(#[from] OwnershipError),
 
+    #[error("{0}")]
+    Verification(#[from] VerificationError),
+
+    #[error("{0}")]
+    Payment(#[from] PaymentError),
+
     #[error("InsufficientFunds")]
     InsufficientFunds {},
 
@@ -34,6 +41,204 @@ pub enum ContractError {
     #[error("DenomNotSupported")]
     DenomNotSupported {},
 
+    #[error("InvalidMaxSupply")]
+    InvalidMaxSupply {},
+
+    #[error("MaxSupplyBelowMinted")]
+    MaxSupplyBelowMinted {},
+
+    #[error("InvalidOpenEdition")]
+    InvalidOpenEdition {},
+
+    #[error("SaleNotEnded")]
+    SaleNotEnded {},
+
+    #[error("LaunchFinalized")]
+    LaunchFinalized {},
+
+    #[error("InvalidUriTemplate")]
+    InvalidUriTemplate {},
+
+    #[error("InvalidTokenIdScheme")]
+    InvalidTokenIdScheme {},
+
+    #[error("DuplicateTokenId")]
+    DuplicateTokenId {},
+
+    #[error("TokenIdsExhausted")]
+    TokenIdsExhausted {},
+
+    #[error("InvalidProvenanceHash")]
+    InvalidProvenanceHash {},
+
+    #[error("ProvenanceNotSet")]
+    ProvenanceNotSet {},
+
+    #[error("StartingIndexSet")]
+    StartingIndexSet {},
+
+    #[error("InvalidTiers")]
+    InvalidTiers {},
+
+    #[error("UnknownTier")]
+    UnknownTier {},
+
+    #[error("InvalidSchedule")]
+    InvalidSchedule {},
+
+    #[error("OverlappingPhases")]
+    OverlappingPhases {},
+
+    #[error("MissingWhitelistSource")]
+    MissingWhitelistSource {},
+
+    #[error("PhaseActive")]
+    PhaseActive {},
+
+    #[error("LaunchLocked")]
+    LaunchLocked {},
+
+    #[error("AlreadyLocked")]
+    AlreadyLocked {},
+
+    #[error("InvalidExpiration")]
+    InvalidExpiration {},
+
+    #[error("LaunchTransferNotFound")]
+    LaunchTransferNotFound {},
+
+    #[error("NotPendingLaunchOwner")]
+    NotPendingLaunchOwner {},
+
+    #[error("LaunchTransferExpired")]
+    LaunchTransferExpired {},
+
+    #[error("InvalidReservedSupply")]
+    InvalidReservedSupply {},
+
+    #[error("ReservedSupplyExhausted")]
+    ReservedSupplyExhausted {},
+
+    #[error("EmptyAirdrop")]
+    EmptyAirdrop {},
+
+    #[error("InvalidQuantity")]
+    InvalidQuantity {},
+
+    #[error("MintLimitExceeded")]
+    MintLimitExceeded {},
+
+    #[error("ContractCallerRejected")]
+    ContractCallerRejected {},
+
+    #[error("InvalidHolderGate")]
+    InvalidHolderGate {},
+
+    #[error("NotHolder")]
+    NotHolder {},
+
+    #[error("MissingGateToken")]
+    MissingGateToken {},
+
+    #[error("GateTokenExhausted")]
+    GateTokenExhausted {},
+
+    #[error("InvalidBalanceGate")]
+    InvalidBalanceGate {},
+
+    #[error("InsufficientBalance")]
+    InsufficientBalance {},
+
+    #[error("InvalidRaffle")]
+    InvalidRaffle {},
+
+    #[error("RaffleClosed")]
+    RaffleClosed {},
+
+    #[error("AlreadyRegistered")]
+    AlreadyRegistered {},
+
+    #[error("NotRegistered")]
+    NotRegistered {},
+
+    #[error("RaffleNotDrawn")]
+    RaffleNotDrawn {},
+
+    #[error("RaffleAlreadyDrawn")]
+    RaffleAlreadyDrawn {},
+
+    #[error("RaffleExpired")]
+    RaffleExpired {},
+
+    #[error("RaffleInProgress")]
+    RaffleInProgress {},
+
+    #[error("InvalidSeed")]
+    InvalidSeed {},
+
+    #[error("NotRaffleWinner")]
+    NotRaffleWinner {},
+
+    #[error("RandomnessBeaconNotSet")]
+    RandomnessBeaconNotSet {},
+
+    #[error("RandomnessPending")]
+    RandomnessPending {},
+
+    #[error("RandomnessNotPending")]
+    RandomnessNotPending {},
+
+    #[error("UnknownRandomnessJob")]
+    UnknownRandomnessJob {},
+
+    #[error("TradeInNotAccepted")]
+    TradeInNotAccepted {},
+
+    #[error("InvalidTakerFee")]
+    InvalidTakerFee {},
+
+    #[error("InvalidFeeTiers")]
+    InvalidFeeTiers {},
+
+    #[error("InvalidReferralShare")]
+    InvalidReferralShare {},
+
+    #[error("SelfReferral")]
+    SelfReferral {},
+
+    #[error("NothingToWithdraw")]
+    NothingToWithdraw {},
+
+    #[error("InvalidPublicKey")]
+    InvalidPublicKey {},
+
+    #[error("InvalidVoucher")]
+    InvalidVoucher {},
+
+    #[error("VoucherExpired")]
+    VoucherExpired {},
+
+    #[error("VoucherExhausted")]
+    VoucherExhausted {},
+
+    #[error("MissingAllocation")]
+    MissingAllocation {},
+
+    #[error("InvalidAllocation")]
+    InvalidAllocation {},
+
+    #[error("MerkleRootNotSet")]
+    MerkleRootNotSet {},
+
+    #[error("InvalidMerkleRoot")]
+    InvalidMerkleRoot {},
+
+    #[error("InvalidHex")]
+    InvalidHex {},
+
+    #[error("ProofTooDeep")]
+    ProofTooDeep {},
+
     #[error("Wrong Length")]
     WrongLength {},
 
@@ -1,7 +1,10 @@
 pub mod contract;
 mod error;
 pub mod helpers;
+mod merkle;
 pub mod msg;
+mod randomness;
 pub mod state;
+mod uri;
 
 pub use crate::error::ContractError;
@@ -0,0 +1,170 @@
+use sha2::Digest;
+
+use crate::state::MerkleScheme;
+use crate::ContractError;
+
+// enough for 2^32 allowlisted addresses
+pub const MAX_PROOF_DEPTH: usize = 32;
+
+const LEAF_PREFIX: u8 = 0x00;
+const NODE_PREFIX: u8 = 0x01;
+const LEAF_DOMAIN: &[u8] = b"cw721-launchpad:v2:";
+
+fn sha256(data: &[u8]) -> [u8; 32] {
+    let mut hash = [0; 32];
+    hash.copy_from_slice(&sha2::Sha256::digest(data));
+    hash
+}
+
+fn hash_leaf(scheme: &MerkleScheme, contract_address: &str, leaf: &str) -> [u8; 32] {
+    match scheme {
+        MerkleScheme::V1 => sha256(leaf.as_bytes()),
+        MerkleScheme::V2 => sha256(
+            &[
+                &[LEAF_PREFIX],
+                LEAF_DOMAIN,
+                contract_address.as_bytes(),
+                b":",
+                leaf.as_bytes(),
+            ]
+            .concat(),
+        ),
+    }
+}
+
+fn hash_node(scheme: &MerkleScheme, left: [u8; 32], right: [u8; 32]) -> [u8; 32] {
+    let mut hashes = [left, right];
+    hashes.sort_unstable();
+    match scheme {
+        MerkleScheme::V1 => sha256(&hashes.concat()),
+        MerkleScheme::V2 => sha256(&[&[NODE_PREFIX][..], &hashes[0], &hashes[1]].concat()),
+    }
+}
+
+pub fn parse_hash(hex_hash: &str) -> Result<[u8; 32], ContractError> {
+    let mut hash = [0; 32];
+    hex::decode_to_slice(hex_hash, &mut hash).map_err(|err| match err {
+        hex::FromHexError::InvalidHexCharacter { .. } => ContractError::InvalidHex {},
+        _ => ContractError::WrongLength {},
+    })?;
+
+    Ok(hash)
+}
+
+pub fn validate_merkle_root(merkle_root: &str) -> Result<(), ContractError> {
+    parse_hash(merkle_root).map_err(|_| ContractError::InvalidMerkleRoot {})?;
+
+    Ok(())
+}
+
+pub fn verify_merkle_proof(
+    scheme: &MerkleScheme,
+    contract_address: &str,
+    leaf: &str,
+    merkle_root: &str,
+    proof: &[String],
+) -> Result<(), ContractError> {
+    // https://github.com/CosmWasm/cw-tokens/blob/main/contracts/cw20-merkle-airdrop/src/contract.rs#L282
+    if proof.len() > MAX_PROOF_DEPTH {
+        return Err(ContractError::ProofTooDeep {});
+    }
+    let root = parse_hash(merkle_root)?;
+
+    let hash = proof
+        .iter()
+        .try_fold(hash_leaf(scheme, contract_address, leaf), |hash, p| {
+            Ok::<_, ContractError>(hash_node(scheme, hash, parse_hash(p)?))
+        })?;
+
+    if root != hash {
+        return Err(ContractError::VerificationFailed {});
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tree() -> (String, Vec<String>) {
+        let buyer = sha256(b"buyer");
+        let other = sha256(b"other");
+        let mut hashes = [buyer, other];
+        hashes.sort_unstable();
+        (
+            hex::encode(sha256(&hashes.concat())),
+            vec![hex::encode(other)],
+        )
+    }
+
+    #[test]
+    fn verifies_proof() {
+        let (root, proof) = tree();
+
+        verify_merkle_proof(&MerkleScheme::V1, "collection", "buyer", &root, &proof).unwrap();
+        assert!(matches!(
+            verify_merkle_proof(&MerkleScheme::V1, "collection", "stranger", &root, &proof),
+            Err(ContractError::VerificationFailed {})
+        ));
+    }
+
+    #[test]
+    fn v2_proofs_are_bound_to_their_launch() {
+        let buyer = hash_leaf(&MerkleScheme::V2, "collection", "buyer");
+        let other = hash_leaf(&MerkleScheme::V2, "collection", "other");
+        let root = hex::encode(hash_node(&MerkleScheme::V2, buyer, other));
+        let proof = vec![hex::encode(other)];
+
+        verify_merkle_proof(&MerkleScheme::V2, "collection", "buyer", &root, &proof).unwrap();
+        assert!(matches!(
+            verify_merkle_proof(&MerkleScheme::V2, "another", "buyer", &root, &proof),
+            Err(ContractError::VerificationFailed {})
+        ));
+        // a v1 leaf does not verify against a v2 tree
+        assert!(matches!(
+            verify_merkle_proof(&MerkleScheme::V1, "collection", "buyer", &root, &proof),
+            Err(ContractError::VerificationFailed {})
+        ));
+    }
+
+    #[test]
+    fn rejects_malformed_input() {
+        let (root, proof) = tree();
+
+        assert!(matches!(
+            verify_merkle_proof(
+                &MerkleScheme::V1,
+                "collection",
+                "buyer",
+                &root,
+                &["zz".repeat(32)]
+            ),
+            Err(ContractError::InvalidHex {})
+        ));
+        assert!(matches!(
+            verify_merkle_proof(
+                &MerkleScheme::V1,
+                "collection",
+                "buyer",
+                &root,
+                &["ab".to_string()]
+            ),
+            Err(ContractError::WrongLength {})
+        ));
+        assert!(matches!(
+            verify_merkle_proof(
+                &MerkleScheme::V1,
+                "collection",
+                "buyer",
+                &root,
+                &vec![proof[0].clone(); MAX_PROOF_DEPTH + 1]
+            ),
+            Err(ContractError::ProofTooDeep {})
+        ));
+        assert!(matches!(
+            validate_merkle_root(&root[1..]),
+            Err(ContractError::InvalidMerkleRoot {})
+        ));
+    }
+}
@@ -1,9 +1,13 @@
 use cosmwasm_schema::{cw_serde, QueryResponses};
-use cosmwasm_std::{Coin, Uint64};
+use cosmwasm_std::{Addr, Binary, Coin, Timestamp, Uint128, Uint64};
+use cw721::Cw721ReceiveMsg;
 use cw_ownable::cw_ownable_execute;
+use cw_utils::Expiration;
 
-#[allow(unused_imports)]
-use crate::state::Launch;
+use crate::state::{
+    BalanceGate, FeeTier, HolderGate, Launch, LeafFormat, LockPolicy, MerkleScheme, MintLimits,
+    Phase, RaffleConfig, ReferralConfig, Tier, TokenIdScheme, TradeIn, VoucherSigner,
+};
 
 #[cw_serde]
 pub struct InstantiateMsg {
@@ -18,18 +22,36 @@ pub enum ExecuteMsg {
     ChangeTakerFee {
         taker_fee: Uint64,
     },
+    ChangeLaunchTakerFee {
+        contract_address: String,
+        #[serde(default)]
+        taker_fee: Update<u64>,
+        #[serde(default)]
+        taker_fee_tiers: Update<Vec<FeeTier>>,
+    },
     Mint {
         contract_address: String,
         receiver_address: Option<String>,
         proof: Option<Vec<String>>,
+        referrer: Option<String>,
+        voucher: Option<Voucher>,
+        allocation: Option<WhitelistAllocation>,
+        // defaults to a single token
+        quantity: Option<u32>,
+        // token of the gating collection held by the sender
+        gate_token: Option<String>,
+        // required by tiered launches
+        tier: Option<String>,
     },
     AddLaunch {
         owner_address: String,
         contract_address: String,
-        max_supply: u64,
+        max_supply: Option<u64>,
         base_uri: String,
         is_base_uri_static: bool,
         media_extension: Option<String>,
+        // placeholders {id}, {id:0N}, {shuffled_id} and {tier}
+        uri_template: Option<String>,
         whitelist_price: Coin,
         whitelist_max_buy: Option<u16>,
         whitelist_started_at: Uint64,
@@ -41,32 +63,239 @@ pub enum ExecuteMsg {
         royalty_percentage: Option<u64>,
         royalty_payment_address: Option<String>,
         whitelist_merkle_root: Option<String>,
+        referral: Option<ReferralConfig>,
+        whitelist_signer: Option<VoucherSigner>,
+        whitelist_leaf_format: Option<LeafFormat>,
+        whitelist_merkle_scheme: Option<MerkleScheme>,
+        lock_policy: Option<LockPolicy>,
+        reserved_supply: Option<u64>,
+        free_mint_fee: Option<Coin>,
+        mint_limits: Option<MintLimits>,
+        whitelist_holder_gate: Option<HolderGate>,
+        public_holder_gate: Option<HolderGate>,
+        trade_in: Option<TradeIn>,
+        whitelist_balance_gate: Option<BalanceGate>,
+        public_balance_gate: Option<BalanceGate>,
+        raffle: Option<RaffleConfig>,
+        tiers: Option<Vec<Tier>>,
+        token_id_scheme: Option<TokenIdScheme>,
+        provenance_hash: Option<String>,
     },
     RemoveLaunch {
         contract_address: String,
+        reason: Option<String>,
     },
+    // required fields are kept when None, optional ones use `Update`
     ModifyLaunch {
         contract_address: String,
-        max_supply: Option<u64>,
+        #[serde(default)]
+        max_supply: Update<u64>,
         base_uri: Option<String>,
         is_base_uri_static: Option<bool>,
-        media_extension: Option<String>,
+        #[serde(default)]
+        media_extension: Update<String>,
+        #[serde(default)]
+        uri_template: Update<String>,
         whitelist_price: Option<Coin>,
-        whitelist_max_buy: Option<u16>,
+        #[serde(default)]
+        whitelist_max_buy: Update<u16>,
         whitelist_started_at: Option<Uint64>,
         whitelist_ended_at: Option<Uint64>,
         public_price: Option<Coin>,
-        public_max_buy: Option<u16>,
+        #[serde(default)]
+        public_max_buy: Update<u16>,
         public_started_at: Option<Uint64>,
         public_ended_at: Option<Uint64>,
-        whitelist_merkle_root: Option<String>,
+        #[serde(default)]
+        royalty_percentage: Update<u64>,
+        #[serde(default)]
+        royalty_payment_address: Update<String>,
+        #[serde(default)]
+        whitelist_merkle_root: Update<String>,
+        #[serde(default)]
+        referral: Update<ReferralConfig>,
+        #[serde(default)]
+        whitelist_signer: Update<VoucherSigner>,
+        #[serde(default)]
+        whitelist_leaf_format: Update<LeafFormat>,
+        #[serde(default)]
+        whitelist_merkle_scheme: Update<MerkleScheme>,
+        #[serde(default)]
+        lock_policy: Update<LockPolicy>,
+        #[serde(default)]
+        reserved_supply: Update<u64>,
+        #[serde(default)]
+        free_mint_fee: Update<Coin>,
+        #[serde(default)]
+        mint_limits: Update<MintLimits>,
+        #[serde(default)]
+        whitelist_holder_gate: Update<HolderGate>,
+        #[serde(default)]
+        public_holder_gate: Update<HolderGate>,
+        #[serde(default)]
+        trade_in: Update<TradeIn>,
+        #[serde(default)]
+        whitelist_balance_gate: Update<BalanceGate>,
+        #[serde(default)]
+        public_balance_gate: Update<BalanceGate>,
+        #[serde(default)]
+        raffle: Update<RaffleConfig>,
+        #[serde(default)]
+        tiers: Update<Vec<Tier>>,
+        #[serde(default)]
+        token_id_scheme: Update<TokenIdScheme>,
+        #[serde(default)]
+        provenance_hash: Update<String>,
+        // allow price changes while the affected phase is live
+        force: Option<bool>,
+    },
+    LockLaunch {
+        contract_address: String,
+    },
+    // records the edition size of an open edition once its sale ended
+    FinalizeLaunch {
+        contract_address: String,
+    },
+    // derives the starting index of a provenance launch whose sale ended unsold
+    SetStartingIndex {
+        contract_address: String,
+    },
+    // free mints out of the reserved supply, as (recipient, quantity)
+    AirdropMint {
+        contract_address: String,
+        recipients: Vec<(String, u32)>,
+    },
+    TransferLaunchOwnership {
+        contract_address: String,
+        new_owner: String,
+        expiry: Option<Expiration>,
+    },
+    AcceptLaunchOwnership {
+        contract_address: String,
+    },
+    WithdrawReferralRewards {},
+    // cw721 hook, the sent token pays for a mint of a trade-in launch
+    ReceiveNft(Cw721ReceiveMsg),
+    // prepays the phase price of trade-in mints
+    DepositTradeInFunds {
+        contract_address: String,
+    },
+    WithdrawTradeInFunds {
+        contract_address: String,
+    },
+    // deposits the public price for a raffle ticket
+    Register {
+        contract_address: String,
+    },
+    // reveals the committed seed, or asks the randomness beacon when there is none
+    DrawRaffle {
+        contract_address: String,
+        seed: Option<String>,
+    },
+    // drops a beacon request that never got answered so the draw can be asked for again
+    CancelRaffleDraw {
+        contract_address: String,
+    },
+    ClaimRaffleMint {
+        contract_address: String,
+    },
+    WithdrawRaffleDeposit {
+        contract_address: String,
+    },
+    SetRandomnessBeacon {
+        beacon: Option<String>,
+    },
+    // randomness callback of the beacon
+    NoisReceive {
+        callback: RandomnessCallback,
+    },
+    // appends to the ids of a launch with the list token id scheme
+    AddTokenIds {
+        contract_address: String,
+        token_ids: Vec<String>,
+    },
+    AddToAllowlist {
+        contract_address: String,
+        phase: Phase,
+        entries: Vec<AllowlistEntryInfo>,
+    },
+    RemoveFromAllowlist {
+        contract_address: String,
+        phase: Phase,
+        addresses: Vec<String>,
     },
 }
 
+#[cw_serde]
+pub enum ReceiveNftMsg {
+    TradeIn {
+        contract_address: String,
+        receiver_address: Option<String>,
+        // token of the gating collection held by the sender
+        gate_token: Option<String>,
+    },
+}
+
+/// Change to an optional `Launch` field. Omitted fields are kept.
+#[cw_serde]
+#[derive(Default)]
+pub enum Update<T> {
+    #[default]
+    Keep,
+    Set(T),
+    Clear,
+}
+
+impl<T> Update<T> {
+    pub fn apply(self, field: &mut Option<T>) {
+        match self {
+            Update::Keep => {}
+            Update::Set(value) => *field = Some(value),
+            Update::Clear => *field = None,
+        }
+    }
+}
+
+/// Allowlist voucher issued off-chain by the launch's `whitelist_signer`.
+/// The signature covers the sha256 hash of the JSON encoded `VoucherPayload`.
+#[cw_serde]
+pub struct Voucher {
+    pub wallet: String,
+    pub launch: String,
+    pub phase: Phase,
+    pub max_quantity: u32,
+    pub expires_at: Uint64,
+    pub nonce: String,
+    pub signature: Binary,
+}
+
+#[cw_serde]
+pub struct VoucherPayload {
+    pub wallet: String,
+    pub launch: String,
+    pub phase: Phase,
+    pub max_quantity: u32,
+    pub expires_at: Uint64,
+    pub nonce: String,
+}
+
+impl Voucher {
+    pub fn payload(&self) -> VoucherPayload {
+        VoucherPayload {
+            wallet: self.wallet.clone(),
+            launch: self.launch.clone(),
+            phase: self.phase.clone(),
+            max_quantity: self.max_quantity,
+            expires_at: self.expires_at,
+            nonce: self.nonce.clone(),
+        }
+    }
+}
+
 #[cw_serde]
 #[derive(QueryResponses)]
 pub enum QueryMsg {
-    #[returns(Launch)]
+    #[returns(LaunchResponse)]
     GetLaunch { contract_address: String },
     #[returns(LaunchStatus)]
     GetLaunchStatus { contract_address: String },
@@ -75,20 +304,196 @@ pub enum QueryMsg {
         contract_address: String,
         account_address: String,
         proof: Vec<String>,
+        allocation: Option<WhitelistAllocation>,
+    },
+    #[returns(ReferralsResponse)]
+    GetReferrals {
+        contract_address: String,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    #[returns(ReferralRewardsResponse)]
+    GetReferralRewards { address: String },
+    #[returns(AllowlistResponse)]
+    GetAllowlist {
+        contract_address: String,
+        phase: Phase,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    #[returns(LaunchOwnershipResponse)]
+    GetLaunchOwnership { contract_address: String },
+    #[returns(ArchivedLaunchInfo)]
+    GetArchivedLaunch { contract_address: String },
+    #[returns(ArchivedLaunchesResponse)]
+    GetArchivedLaunches {
+        start_after: Option<String>,
+        limit: Option<u32>,
     },
+    #[returns(RaffleResponse)]
+    GetRaffle {
+        contract_address: String,
+        address: Option<String>,
+    },
+    // token id and uri the number-th token of the launch or tier would be minted with
+    #[returns(TokenUriResponse)]
+    PreviewTokenUri {
+        contract_address: String,
+        number: u64,
+        tier: Option<String>,
+    },
+    #[returns(ProvenanceResponse)]
+    GetProvenance { contract_address: String },
+}
+
+#[cw_serde]
+pub struct LaunchResponse {
+    pub launch: Launch,
+    pub effective_taker_fee: Uint64,
+    pub taker_fee_tier: Option<FeeTier>,
+    pub is_locked: bool,
+}
+
+#[cw_serde]
+pub struct LaunchOwnershipResponse {
+    pub owner: Addr,
+    pub pending_owner: Option<Addr>,
+    pub pending_expiry: Option<Expiration>,
 }
 
 #[cw_serde]
 pub struct LaunchStatus {
     pub status: String,
+    pub tiers: Vec<TierStatus>,
+}
+
+#[cw_serde]
+pub struct TokenUriResponse {
+    pub token_id: String,
+    pub token_uri: String,
+}
+
+#[cw_serde]
+pub struct TierStatus {
+    pub name: String,
+    pub supply: u64,
+    pub remaining: u64,
+}
+
+/// Per-wallet allocation encoded in a `LeafFormat::AddressAllocation` leaf.
+#[cw_serde]
+pub struct WhitelistAllocation {
+    pub max_quantity: u32,
+    pub price: Uint128,
 }
 
 #[cw_serde]
 pub struct WhitelistStatus {
     pub is_whitelist: bool,
+    pub allocation: Option<WhitelistAllocation>,
+}
+
+#[cw_serde]
+pub struct ReferralInfo {
+    pub referrer: String,
+    pub minted: u64,
+    pub earned: Uint128,
+}
+
+#[cw_serde]
+pub struct ReferralsResponse {
+    pub referrals: Vec<ReferralInfo>,
+}
+
+#[cw_serde]
+pub struct ArchivedLaunchInfo {
+    pub contract_address: Addr,
+    pub launch: Launch,
+    pub reason: Option<String>,
+    pub archived_at: u64,
+}
+
+#[cw_serde]
+pub struct ArchivedLaunchesResponse {
+    pub launches: Vec<ArchivedLaunchInfo>,
+}
+
+#[cw_serde]
+pub struct ProvenanceResponse {
+    pub provenance_hash: Option<String>,
+    // token n gets the metadata at (n - 1 + starting_index) % supply of the provenance order
+    pub starting_index: Option<u64>,
+}
+
+#[cw_serde]
+pub struct RaffleResponse {
+    pub registrants: u32,
+    pub winners: u32,
+    pub drawn: bool,
+    pub entry: Option<RaffleEntryInfo>,
+}
+
+#[cw_serde]
+pub struct RaffleEntryInfo {
+    pub deposit: Uint128,
+    // known once the raffle is drawn
+    pub won: Option<bool>,
+}
+
+#[cw_serde]
+pub struct ReferralRewardsResponse {
+    pub pending: Coin,
+}
+
+#[cw_serde]
+pub struct AllowlistEntryInfo {
+    pub address: String,
+    pub max_quantity: Option<u32>,
+}
+
+#[cw_serde]
+pub struct AllowlistResponse {
+    pub entries: Vec<AllowlistEntryInfo>,
 }
 
 #[cw_serde]
 pub enum MigrateMsg {
     Migrate {},
 }
+
+// subset of the cw20 and cw20-stake queries used by balance gates
+#[cw_serde]
+pub enum BalanceGateQueryMsg {
+    Balance {
+        address: String,
+    },
+    StakedBalanceAtHeight {
+        address: String,
+        height: Option<u64>,
+    },
+}
+
+#[cw_serde]
+pub struct BalanceResponse {
+    pub balance: Uint128,
+}
+
+#[cw_serde]
+pub struct StakedBalanceAtHeightResponse {
+    pub balance: Uint128,
+    pub height: u64,
+}
+
+// randomness request understood by the beacon, Nois-style
+#[cw_serde]
+pub enum BeaconExecuteMsg {
+    GetNextRandomness { job_id: String },
+}
+
+#[cw_serde]
+pub struct RandomnessCallback {
+    pub job_id: String,
+    pub published: Timestamp,
+    // hex encoded 32 bytes
+    pub randomness: String,
+}
@@ -0,0 +1,354 @@
+use cosmwasm_std::{to_binary, Addr, Coin, CosmosMsg, Storage, WasmMsg};
+
+use crate::merkle::parse_hash;
+use crate::msg::{BeaconExecuteMsg, RandomnessCallback};
+use crate::state::{RandomnessJob, RANDOMNESS_BEACON, RANDOMNESS_JOBS, RANDOMNESS_JOB_COUNT};
+use crate::ContractError;
+
+// stores the job and builds the beacon request, the funds pay the beacon fee
+pub fn request_randomness(
+    storage: &mut dyn Storage,
+    job: &RandomnessJob,
+    funds: Vec<Coin>,
+) -> Result<(String, CosmosMsg), ContractError> {
+    let beacon = RANDOMNESS_BEACON
+        .may_load(storage)?
+        .ok_or(ContractError::RandomnessBeaconNotSet {})?;
+
+    let count = RANDOMNESS_JOB_COUNT.may_load(storage)?.unwrap_or_default() + 1;
+    RANDOMNESS_JOB_COUNT.save(storage, &count)?;
+    let job_id = format!("launchpad-{}", count);
+    RANDOMNESS_JOBS.save(storage, &job_id, job)?;
+
+    let request_msg = WasmMsg::Execute {
+        contract_addr: beacon.to_string(),
+        msg: to_binary(&BeaconExecuteMsg::GetNextRandomness {
+            job_id: job_id.clone(),
+        })?,
+        funds,
+    };
+
+    Ok((job_id, request_msg.into()))
+}
+
+// takes the pending job answered by a beacon callback
+pub fn receive_randomness(
+    storage: &mut dyn Storage,
+    sender: &Addr,
+    callback: &RandomnessCallback,
+) -> Result<(RandomnessJob, [u8; 32]), ContractError> {
+    if RANDOMNESS_BEACON.may_load(storage)?.as_ref() != Some(sender) {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let job = RANDOMNESS_JOBS
+        .may_load(storage, &callback.job_id)?
+        .ok_or(ContractError::UnknownRandomnessJob {})?;
+    RANDOMNESS_JOBS.remove(storage, &callback.job_id);
+
+    Ok((job, parse_hash(&callback.randomness)?))
+}
+
+// forgets a pending job, a later callback for it is rejected
+pub fn cancel_randomness(storage: &mut dyn Storage, job_id: &str) {
+    RANDOMNESS_JOBS.remove(storage, job_id);
+}
+
+#[cfg(test)]
+mod tests {
+    use cosmwasm_schema::cw_serde;
+    use cosmwasm_std::{
+        coin, Binary, Deps, DepsMut, Empty, Env, MessageInfo, Response, StdError, StdResult, Uint64,
+    };
+    use cw_multi_test::{App, ContractWrapper, Executor};
+    use cw_storage_plus::Item;
+
+    use crate::contract;
+    use crate::msg::{ExecuteMsg, InstantiateMsg, QueryMsg, RaffleResponse};
+    use crate::state::RaffleConfig;
+
+    use super::*;
+
+    // local beacon stand-in, it answers the last request when told to publish
+    #[cw_serde]
+    enum MockBeaconMsg {
+        GetNextRandomness { job_id: String },
+        Publish { randomness: String },
+    }
+
+    const REQUEST: Item<(Addr, String)> = Item::new("request");
+
+    fn beacon_instantiate(
+        _deps: DepsMut,
+        _env: Env,
+        _info: MessageInfo,
+        _msg: Empty,
+    ) -> StdResult<Response> {
+        Ok(Response::new())
+    }
+
+    fn beacon_execute(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        msg: MockBeaconMsg,
+    ) -> StdResult<Response> {
+        match msg {
+            MockBeaconMsg::GetNextRandomness { job_id } => {
+                REQUEST.save(deps.storage, &(info.sender, job_id))?;
+                Ok(Response::new())
+            }
+            MockBeaconMsg::Publish { randomness } => {
+                let (requester, job_id) = REQUEST.load(deps.storage)?;
+                REQUEST.remove(deps.storage);
+                Ok(Response::new().add_message(WasmMsg::Execute {
+                    contract_addr: requester.to_string(),
+                    msg: to_binary(&ExecuteMsg::NoisReceive {
+                        callback: RandomnessCallback {
+                            job_id,
+                            published: env.block.time,
+                            randomness,
+                        },
+                    })?,
+                    funds: vec![],
+                }))
+            }
+        }
+    }
+
+    fn beacon_query(_deps: Deps, _env: Env, _msg: Empty) -> StdResult<Binary> {
+        Err(StdError::generic_err("no queries"))
+    }
+
+    // launchpad and beacon with three registrants past the registration end
+    fn setup_raffle() -> (App, Addr, Addr) {
+        let mut app = App::default();
+        let owner = Addr::unchecked("owner");
+
+        let launchpad_code = app.store_code(Box::new(ContractWrapper::new(
+            contract::execute,
+            contract::instantiate,
+            contract::query,
+        )));
+        let beacon_code = app.store_code(Box::new(ContractWrapper::new(
+            beacon_execute,
+            beacon_instantiate,
+            beacon_query,
+        )));
+        let launchpad = app
+            .instantiate_contract(
+                launchpad_code,
+                owner.clone(),
+                &InstantiateMsg {
+                    taker_fee: Uint64::new(5),
+                    native_denom: "aconst".to_string(),
+                    taker_address: "taker".to_string(),
+                },
+                &[],
+                "launchpad",
+                None,
+            )
+            .unwrap();
+        let beacon = app
+            .instantiate_contract(beacon_code, owner.clone(), &Empty {}, &[], "beacon", None)
+            .unwrap();
+
+        app.execute_contract(
+            owner.clone(),
+            launchpad.clone(),
+            &ExecuteMsg::SetRandomnessBeacon {
+                beacon: Some(beacon.to_string()),
+            },
+            &[],
+        )
+        .unwrap();
+
+        // a free raffle without a seed commitment
+        let now = app.block_info().time.seconds();
+        let mut add_launch = contract::tests::add_launch_msg();
+        if let ExecuteMsg::AddLaunch {
+            whitelist_price,
+            public_price,
+            public_started_at,
+            public_ended_at,
+            raffle,
+            ..
+        } = &mut add_launch
+        {
+            *whitelist_price = coin(0, "aconst");
+            *public_price = coin(0, "aconst");
+            *public_started_at = Uint64::zero();
+            *public_ended_at = Uint64::zero();
+            *raffle = Some(RaffleConfig {
+                registration_started_at: now,
+                registration_ended_at: now + 100,
+                draw_deadline: now + 1000,
+                winners: 2,
+                seed_commitment: None,
+            });
+        }
+        app.execute_contract(owner.clone(), launchpad.clone(), &add_launch, &[])
+            .unwrap();
+
+        let registrants = ["alice", "bob", "carol"];
+        for registrant in registrants {
+            app.execute_contract(
+                Addr::unchecked(registrant),
+                launchpad.clone(),
+                &ExecuteMsg::Register {
+                    contract_address: "collection".to_string(),
+                },
+                &[],
+            )
+            .unwrap();
+        }
+        app.update_block(|block| block.time = block.time.plus_seconds(100));
+
+        (app, launchpad, beacon)
+    }
+
+    #[test]
+    fn raffle_draw_waits_for_beacon() {
+        let (mut app, launchpad, beacon) = setup_raffle();
+        let owner = Addr::unchecked("owner");
+        let registrants = ["alice", "bob", "carol"];
+
+        let draw = ExecuteMsg::DrawRaffle {
+            contract_address: "collection".to_string(),
+            seed: None,
+        };
+        app.execute_contract(owner.clone(), launchpad.clone(), &draw, &[])
+            .unwrap();
+        let err = app
+            .execute_contract(owner.clone(), launchpad.clone(), &draw, &[])
+            .unwrap_err();
+        assert_eq!(
+            err.root_cause().to_string(),
+            ContractError::RandomnessPending {}.to_string()
+        );
+
+        // only the beacon may deliver randomness
+        let err = app
+            .execute_contract(
+                owner.clone(),
+                launchpad.clone(),
+                &ExecuteMsg::NoisReceive {
+                    callback: RandomnessCallback {
+                        job_id: "launchpad-1".to_string(),
+                        published: app.block_info().time,
+                        randomness: "ab".repeat(32),
+                    },
+                },
+                &[],
+            )
+            .unwrap_err();
+        assert_eq!(
+            err.root_cause().to_string(),
+            ContractError::Unauthorized {}.to_string()
+        );
+
+        app.execute_contract(
+            owner,
+            beacon,
+            &MockBeaconMsg::Publish {
+                randomness: "ab".repeat(32),
+            },
+            &[],
+        )
+        .unwrap();
+
+        let mut winners = 0;
+        for registrant in registrants {
+            let raffle: RaffleResponse = app
+                .wrap()
+                .query_wasm_smart(
+                    launchpad.clone(),
+                    &QueryMsg::GetRaffle {
+                        contract_address: "collection".to_string(),
+                        address: Some(registrant.to_string()),
+                    },
+                )
+                .unwrap();
+            assert!(raffle.drawn);
+            if raffle.entry.unwrap().won.unwrap() {
+                winners += 1;
+            }
+        }
+        assert_eq!(winners, 2);
+    }
+
+    #[test]
+    fn stuck_raffle_draw_is_cancelled() {
+        let (mut app, launchpad, beacon) = setup_raffle();
+        let owner = Addr::unchecked("owner");
+
+        let draw = ExecuteMsg::DrawRaffle {
+            contract_address: "collection".to_string(),
+            seed: None,
+        };
+        let cancel = ExecuteMsg::CancelRaffleDraw {
+            contract_address: "collection".to_string(),
+        };
+        let err = app
+            .execute_contract(owner.clone(), launchpad.clone(), &cancel, &[])
+            .unwrap_err();
+        assert_eq!(
+            err.root_cause().to_string(),
+            ContractError::RandomnessNotPending {}.to_string()
+        );
+
+        // the beacon never publishes for the first request
+        app.execute_contract(owner.clone(), launchpad.clone(), &draw, &[])
+            .unwrap();
+        let err = app
+            .execute_contract(Addr::unchecked("alice"), launchpad.clone(), &cancel, &[])
+            .unwrap_err();
+        assert_eq!(
+            err.root_cause().to_string(),
+            ContractError::Unauthorized {}.to_string()
+        );
+        app.execute_contract(owner.clone(), launchpad.clone(), &cancel, &[])
+            .unwrap();
+
+        // a late answer to the cancelled job is rejected
+        let late_callback = ExecuteMsg::NoisReceive {
+            callback: RandomnessCallback {
+                job_id: "launchpad-1".to_string(),
+                published: app.block_info().time,
+                randomness: "ab".repeat(32),
+            },
+        };
+        let err = app
+            .execute_contract(beacon.clone(), launchpad.clone(), &late_callback, &[])
+            .unwrap_err();
+        assert_eq!(
+            err.root_cause().to_string(),
+            ContractError::UnknownRandomnessJob {}.to_string()
+        );
+
+        app.execute_contract(owner.clone(), launchpad.clone(), &draw, &[])
+            .unwrap();
+        app.execute_contract(
+            owner,
+            beacon,
+            &MockBeaconMsg::Publish {
+                randomness: "cd".repeat(32),
+            },
+            &[],
+        )
+        .unwrap();
+
+        let raffle: RaffleResponse = app
+            .wrap()
+            .query_wasm_smart(
+                launchpad,
+                &QueryMsg::GetRaffle {
+                    contract_address: "collection".to_string(),
+                    address: None,
+                },
+            )
+            .unwrap();
+        assert!(raffle.drawn);
+        assert_eq!(raffle.winners, 2);
+    }
+}
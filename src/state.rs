@@ -1,15 +1,19 @@
 use cosmwasm_schema::cw_serde;
 
-use cosmwasm_std::{Addr, Coin};
+use cosmwasm_std::{Addr, Binary, Coin, Uint128};
 use cw_storage_plus::{Item, Map};
+use cw_utils::Expiration;
 
 #[cw_serde]
 pub struct Launch {
     pub owner_address: Addr,
-    pub max_supply: u64,
+    // open edition when unset, the supply is whatever sells
+    pub max_supply: Option<u64>,
     pub base_uri: String,
     pub is_base_uri_static: bool,
     pub media_extension: Option<String>,
+    // builds token uris from placeholders instead of base_uri, see uri.rs
+    pub uri_template: Option<String>,
     pub whitelist_price: Coin,
     pub whitelist_max_buy: Option<u16>,
     pub whitelist_started_at: u64,
@@ -23,6 +27,258 @@ pub struct Launch {
     // https://github.com/CosmWasm/cw-nfts/blob/main/contracts/cw2981-royalties/src/lib.rs#L45
     pub royalty_payment_address: Option<String>,
     pub whitelist_merkle_root: Option<String>,
+    pub taker_fee: Option<u64>,
+    pub taker_fee_tiers: Option<Vec<FeeTier>>,
+    pub referral: Option<ReferralConfig>,
+    pub whitelist_signer: Option<VoucherSigner>,
+    pub whitelist_leaf_format: Option<LeafFormat>,
+    pub whitelist_merkle_scheme: Option<MerkleScheme>,
+    pub lock_policy: Option<LockPolicy>,
+    // set on the first mint or by an explicit LockLaunch
+    pub locked_at: Option<u64>,
+    // tokens set aside for AirdropMint that buyers can not mint
+    pub reserved_supply: Option<u64>,
+    #[serde(default)]
+    pub reserved_minted: u64,
+    // tokens minted through sales since the launch was added, the fee tiers count these
+    #[serde(default)]
+    pub sold: u64,
+    // anti-bot fee for phases priced at zero, paid to the taker only
+    pub free_mint_fee: Option<Coin>,
+    pub mint_limits: Option<MintLimits>,
+    pub whitelist_holder_gate: Option<HolderGate>,
+    pub public_holder_gate: Option<HolderGate>,
+    pub trade_in: Option<TradeIn>,
+    pub whitelist_balance_gate: Option<BalanceGate>,
+    pub public_balance_gate: Option<BalanceGate>,
+    pub raffle: Option<RaffleConfig>,
+    // final supply of an open edition, recorded by FinalizeLaunch
+    pub edition_size: Option<u64>,
+    pub tiers: Option<Vec<Tier>>,
+    // ids of tokens minted outside a tier, "1".."max_supply" when unset
+    pub token_id_scheme: Option<TokenIdScheme>,
+    // hex sha256 over the ordered metadata, frozen once the launch is locked
+    pub provenance_hash: Option<String>,
+    // offset into the provenance order, derived at sell-out or after the sale
+    pub starting_index: Option<u64>,
+}
+
+#[cw_serde]
+pub enum Phase {
+    Whitelist,
+    Public,
+}
+
+impl Phase {
+    pub fn as_str(&self) -> &str {
+        match self {
+            Phase::Whitelist => "whitelist",
+            Phase::Public => "public",
+        }
+    }
+}
+
+#[cw_serde]
+pub struct FeeTier {
+    // tier applies once the launch has sold at least this many tokens
+    pub min_sold: u64,
+    pub taker_fee: u64,
+}
+
+#[cw_serde]
+pub struct ReferralConfig {
+    // percentage of the owner proceeds that goes to the referrer
+    pub share_percentage: u64,
+    // keep rewards in the contract until the referrer withdraws them
+    pub accrue: bool,
+}
+
+#[cw_serde]
+#[derive(Default)]
+pub struct ReferralTotal {
+    pub minted: u64,
+    pub earned: Uint128,
+}
+
+// restricts a phase to holders of another cw721 collection
+#[cw_serde]
+pub struct HolderGate {
+    pub collection: Addr,
+    pub min_count: u32,
+    // the minter has to name a token of the collection it owns
+    pub require_token_proof: bool,
+    // mints allowed per gating token, whichever wallet holds it
+    pub max_mints_per_token: Option<u32>,
+}
+
+// derives the wallet quota of a phase from a token balance
+#[cw_serde]
+pub struct BalanceGate {
+    pub source: BalanceSource,
+    // sorted by ascending min_balance, the highest tier reached applies
+    pub tiers: Vec<BalanceTier>,
+    // block height staked balances are read at, required for staking sources
+    // and not allowed for cw20 sources, whose balance is always the live one
+    #[serde(default)]
+    pub snapshot_height: Option<u64>,
+}
+
+#[cw_serde]
+pub enum BalanceSource {
+    // live balance, tokens can be moved between wallets to mint again
+    Cw20 { contract: Addr },
+    // a cw20-stake contract
+    Staking { contract: Addr },
+}
+
+#[cw_serde]
+pub struct BalanceTier {
+    pub min_balance: Uint128,
+    pub max_buy: u32,
+}
+
+#[cw_serde]
+pub enum TokenIdScheme {
+    // the n-th token gets start + n - 1, zero padded to `padding` digits
+    Sequential {
+        start: u64,
+        prefix: Option<String>,
+        padding: Option<u8>,
+    },
+    // ids uploaded with AddTokenIds, minted in upload order
+    List {},
+}
+
+// token type sold out of the launch with its own supply and price
+#[cw_serde]
+pub struct Tier {
+    pub name: String,
+    pub supply: u64,
+    pub price: Coin,
+    // token ids are the prefix followed by the number of the token within the tier
+    pub token_id_prefix: String,
+    // "{id}" is replaced by the number of the token within the tier
+    pub uri_template: String,
+}
+
+// raffle launches sell through registration and a draw instead of mint
+#[cw_serde]
+pub struct RaffleConfig {
+    pub registration_started_at: u64,
+    pub registration_ended_at: u64,
+    // undrawn raffles release every deposit from this time
+    pub draw_deadline: u64,
+    pub winners: u32,
+    // hex sha256 of the seed revealed by the draw, the randomness beacon
+    // seeds the draw when unset
+    pub seed_commitment: Option<String>,
+}
+
+#[cw_serde]
+#[derive(Default)]
+pub struct RaffleState {
+    pub registrants: u32,
+    pub winners: u32,
+    pub seed: Option<String>,
+    // beacon job the draw is waiting for
+    pub pending_job: Option<String>,
+}
+
+#[cw_serde]
+pub struct RaffleEntry {
+    // position in the draw order, the first `winners` entries win
+    pub index: u32,
+    pub deposit: Uint128,
+}
+
+// tokens of an earlier collection sent to the launchpad pay for a mint
+#[cw_serde]
+pub struct TradeIn {
+    pub collection: Addr,
+    // received tokens are burned unless a sink is set
+    pub sink: Option<Addr>,
+}
+
+#[cw_serde]
+pub struct MintLimits {
+    // tokens a single Mint may ask for
+    pub max_per_tx: Option<u32>,
+    // tokens minted by everyone in a single block
+    pub max_per_block: Option<u32>,
+    pub reject_contract_callers: bool,
+}
+
+#[cw_serde]
+pub struct LockPolicy {
+    // max_supply can still be lowered but never raised
+    pub supply_decrease_only: bool,
+    // base_uri, is_base_uri_static and media_extension
+    pub freeze_base_uri: bool,
+    pub freeze_prices: bool,
+}
+
+impl Default for LockPolicy {
+    fn default() -> Self {
+        LockPolicy {
+            supply_decrease_only: true,
+            freeze_base_uri: true,
+            freeze_prices: false,
+        }
+    }
+}
+
+#[cw_serde]
+pub enum SignatureAlgorithm {
+    Secp256k1,
+    Ed25519,
+}
+
+#[cw_serde]
+pub struct VoucherSigner {
+    pub algorithm: SignatureAlgorithm,
+    pub public_key: Binary,
+}
+
+#[cw_serde]
+pub enum LeafFormat {
+    // sha256(address)
+    Address,
+    // sha256(address|max_quantity|price), price in the native denom
+    AddressAllocation,
+}
+
+#[cw_serde]
+pub struct AllowlistEntry {
+    // overrides the phase max buy for this address
+    pub max_quantity: Option<u32>,
+}
+
+#[cw_serde]
+pub enum MerkleScheme {
+    // leaf = sha256(preimage), node = sha256(sorted pair)
+    V1,
+    // leaf = sha256(0x00 | "cw721-launchpad:v2:" | launch address | ":" | preimage),
+    // node = sha256(0x01 | sorted pair)
+    V2,
+}
+
+// action continued once the beacon delivers the randomness of a job
+#[cw_serde]
+pub enum RandomnessJob {
+    DrawRaffle { contract_address: Addr },
+}
+
+#[cw_serde]
+pub struct PendingLaunchOwner {
+    pub new_owner: Addr,
+    pub expiry: Option<Expiration>,
+}
+
+#[cw_serde]
+pub struct ArchivedLaunch {
+    pub launch: Launch,
+    pub reason: Option<String>,
+    pub archived_at: u64,
 }
 
 pub type ContractAddress = Addr;
@@ -31,3 +287,30 @@ pub const TAKERFEE: Item<u64> = Item::new("taker_fee");
 pub const TAKERADDRESS: Item<Addr> = Item::new("taker_address");
 pub const NATIVE_DENOM: Item<String> = Item::new("native_denom");
 pub const LAUNCHES: Map<&Addr, Launch> = Map::new("launches");
+pub const REFERRAL_TOTALS: Map<(&Addr, &Addr), ReferralTotal> = Map::new("referral_totals");
+pub const REFERRAL_REWARDS: Map<&Addr, Uint128> = Map::new("referral_rewards");
+// number of mints already made with a voucher, keyed by launch and nonce
+pub const VOUCHER_USES: Map<(&Addr, &str), u32> = Map::new("voucher_uses");
+// stored allowlist, keyed by launch, phase and account
+pub const ALLOWLIST: Map<(&Addr, &str, &Addr), AllowlistEntry> = Map::new("allowlist");
+// removed launches, kept so a re-added launch resumes its token ids
+pub const ARCHIVED_LAUNCHES: Map<&Addr, ArchivedLaunch> = Map::new("archived_launches");
+// block height and the number of tokens minted in it, per launch
+pub const BLOCK_MINTS: Map<&Addr, (u64, u64)> = Map::new("block_mints");
+// mints made with a gating token, keyed by launch, phase and token id
+pub const GATE_TOKEN_MINTS: Map<(&Addr, &str, &str), u32> = Map::new("gate_token_mints");
+// tokens minted out of a tier, keyed by launch and tier name
+pub const TIER_MINTED: Map<(&Addr, &str), u64> = Map::new("tier_minted");
+// uploaded token ids by position starting at 1, and the position of each id
+pub const TOKEN_IDS: Map<(&Addr, u64), String> = Map::new("token_ids");
+pub const TOKEN_ID_POSITIONS: Map<(&Addr, &str), u64> = Map::new("token_id_positions");
+pub const TOKEN_ID_COUNT: Map<&Addr, u64> = Map::new("token_id_count");
+pub const RAFFLES: Map<&Addr, RaffleState> = Map::new("raffles");
+pub const RAFFLE_ENTRIES: Map<(&Addr, &Addr), RaffleEntry> = Map::new("raffle_entries");
+pub const RAFFLE_REGISTRANTS: Map<(&Addr, u32), Addr> = Map::new("raffle_registrants");
+pub const RANDOMNESS_BEACON: Item<Addr> = Item::new("randomness_beacon");
+pub const RANDOMNESS_JOB_COUNT: Item<u64> = Item::new("randomness_job_count");
+pub const RANDOMNESS_JOBS: Map<&str, RandomnessJob> = Map::new("randomness_jobs");
+// funds set aside for trade-in mints, keyed by launch and depositor
+pub const TRADE_IN_DEPOSITS: Map<(&Addr, &Addr), Uint128> = Map::new("trade_in_deposits");
+pub const PENDING_LAUNCH_OWNERS: Map<&Addr, PendingLaunchOwner> = Map::new("pending_launch_owners");
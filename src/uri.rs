@@ -0,0 +1,241 @@
+use sha2::Digest;
+
+use crate::ContractError;
+
+// widest zero padding a placeholder may ask for, u64::MAX has 20 digits
+const MAX_WIDTH: usize = 20;
+
+#[derive(Debug, PartialEq)]
+enum Placeholder {
+    Id,
+    ShuffledId,
+    Tier,
+}
+
+#[derive(Debug, PartialEq)]
+enum Segment<'a> {
+    Literal(&'a str),
+    Placeholder {
+        placeholder: Placeholder,
+        width: usize,
+    },
+}
+
+// values a template is rendered with, a shuffle range of None rules out {shuffled_id}
+pub struct TokenUriVars<'a> {
+    // position of the token, 1 for the first one
+    pub id: u64,
+    // id the first token is numbered with
+    pub first_id: u64,
+    pub shuffle_key: &'a str,
+    pub shuffle_range: Option<u64>,
+    pub tier: Option<&'a str>,
+}
+
+fn parse_template(template: &str) -> Result<Vec<Segment<'_>>, ContractError> {
+    let mut segments = vec![];
+    let mut rest = template;
+    while let Some(start) = rest.find(['{', '}']) {
+        if rest[start..].starts_with('}') {
+            return Err(ContractError::InvalidUriTemplate {});
+        }
+        let end = rest[start..]
+            .find('}')
+            .ok_or(ContractError::InvalidUriTemplate {})?
+            + start;
+        if start > 0 {
+            segments.push(Segment::Literal(&rest[..start]));
+        }
+
+        // {name} or {name:0N}
+        let (name, width) = match rest[start + 1..end].split_once(':') {
+            Some((name, spec)) => {
+                let width = spec
+                    .strip_prefix('0')
+                    .and_then(|width| width.parse::<usize>().ok())
+                    .filter(|width| (1..=MAX_WIDTH).contains(width))
+                    .ok_or(ContractError::InvalidUriTemplate {})?;
+                (name, width)
+            }
+            None => (&rest[start + 1..end], 0),
+        };
+        let placeholder = match name {
+            "id" => Placeholder::Id,
+            "shuffled_id" => Placeholder::ShuffledId,
+            "tier" if width == 0 => Placeholder::Tier,
+            _ => return Err(ContractError::InvalidUriTemplate {}),
+        };
+        segments.push(Segment::Placeholder { placeholder, width });
+        rest = &rest[end + 1..];
+    }
+    if !rest.is_empty() {
+        segments.push(Segment::Literal(rest));
+    }
+
+    Ok(segments)
+}
+
+pub fn uses_shuffled_id(template: &str) -> bool {
+    parse_template(template).is_ok_and(|segments| {
+        segments.iter().any(|segment| {
+            matches!(
+                segment,
+                Segment::Placeholder {
+                    placeholder: Placeholder::ShuffledId,
+                    ..
+                }
+            )
+        })
+    })
+}
+
+// checks the template renders for every token it can be used for
+pub fn validate_uri_template(
+    template: &str,
+    shuffle_range: Option<u64>,
+    tiered: bool,
+) -> Result<(), ContractError> {
+    for segment in parse_template(template)? {
+        match segment {
+            Segment::Placeholder {
+                placeholder: Placeholder::ShuffledId,
+                ..
+            } if shuffle_range.is_none() => return Err(ContractError::InvalidUriTemplate {}),
+            Segment::Placeholder {
+                placeholder: Placeholder::Tier,
+                ..
+            } if !tiered => return Err(ContractError::InvalidUriTemplate {}),
+            _ => {}
+        }
+    }
+
+    Ok(())
+}
+
+// position of the id in a permutation of 1..=range fixed by the key
+pub fn shuffled_id(shuffle_key: &str, range: u64, id: u64) -> u64 {
+    let hash = sha2::Sha256::digest(format!("{}:{}", shuffle_key, range).as_bytes());
+    let range = u128::from(range);
+    let mut multiplier = u128::from(u64::from_be_bytes(hash[..8].try_into().unwrap())) % range;
+    let offset = u128::from(u64::from_be_bytes(hash[8..16].try_into().unwrap())) % range;
+
+    // an affine map is a permutation when the multiplier is coprime with the range
+    while gcd(multiplier, range) != 1 {
+        multiplier = (multiplier + 1) % range;
+    }
+
+    ((multiplier * u128::from(id - 1) + offset) % range + 1) as u64
+}
+
+fn gcd(a: u128, b: u128) -> u128 {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+// id of the token at the position, positions start at 1
+fn numbered_id(first_id: u64, position: u64) -> Result<u64, ContractError> {
+    position
+        .checked_sub(1)
+        .and_then(|offset| offset.checked_add(first_id))
+        .ok_or(ContractError::TokenIdsExhausted {})
+}
+
+pub fn render_token_uri(template: &str, vars: &TokenUriVars) -> Result<String, ContractError> {
+    let mut token_uri = String::with_capacity(template.len());
+    for segment in parse_template(template)? {
+        let (value, width) = match segment {
+            Segment::Literal(literal) => {
+                token_uri.push_str(literal);
+                continue;
+            }
+            Segment::Placeholder { placeholder, width } => match placeholder {
+                Placeholder::Id => (numbered_id(vars.first_id, vars.id)?.to_string(), width),
+                Placeholder::ShuffledId => {
+                    let range = vars
+                        .shuffle_range
+                        .filter(|range| (1..=*range).contains(&vars.id))
+                        .ok_or(ContractError::InvalidUriTemplate {})?;
+                    let shuffled_id = shuffled_id(vars.shuffle_key, range, vars.id);
+                    (numbered_id(vars.first_id, shuffled_id)?.to_string(), width)
+                }
+                Placeholder::Tier => (
+                    vars.tier
+                        .ok_or(ContractError::InvalidUriTemplate {})?
+                        .to_string(),
+                    width,
+                ),
+            },
+        };
+        token_uri.push_str(&format!("{:0>width$}", value, width = width));
+    }
+
+    Ok(token_uri)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vars(id: u64) -> TokenUriVars<'static> {
+        TokenUriVars {
+            id,
+            first_id: 1,
+            shuffle_key: "collection",
+            shuffle_range: Some(10),
+            tier: Some("gold"),
+        }
+    }
+
+    #[test]
+    fn renders_placeholders() {
+        assert_eq!(
+            render_token_uri("ipfs://cid/{tier}/{id:05}", &vars(7)).unwrap(),
+            "ipfs://cid/gold/00007"
+        );
+        assert_eq!(
+            render_token_uri("ipfs://cid/{id}", &vars(7)).unwrap(),
+            "ipfs://cid/7"
+        );
+
+        let mut shuffled: Vec<_> = (1..=10)
+            .map(|id| {
+                render_token_uri("{shuffled_id}", &vars(id))
+                    .unwrap()
+                    .parse::<u64>()
+                    .unwrap()
+            })
+            .collect();
+        shuffled.sort_unstable();
+        assert_eq!(shuffled, (1..=10).collect::<Vec<_>>());
+
+        for template in ["{id", "id}", "{name}", "{id:5}", "{id:021}", "{tier:03}"] {
+            assert!(matches!(
+                validate_uri_template(template, Some(10), true),
+                Err(ContractError::InvalidUriTemplate {})
+            ));
+        }
+        assert!(validate_uri_template("{shuffled_id}.json", None, false).is_err());
+        assert!(validate_uri_template("{tier}/{id}.json", Some(10), false).is_err());
+    }
+
+    #[test]
+    fn rejects_ids_out_of_range() {
+        let last = TokenUriVars {
+            first_id: u64::MAX,
+            ..vars(1)
+        };
+        assert_eq!(
+            render_token_uri("{id}", &last).unwrap(),
+            u64::MAX.to_string()
+        );
+        for vars in [TokenUriVars { id: 2, ..last }, vars(0)] {
+            assert!(matches!(
+                render_token_uri("{id}", &vars),
+                Err(ContractError::TokenIdsExhausted {})
+            ));
+        }
+    }
+}